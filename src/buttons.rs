@@ -0,0 +1,115 @@
+use crate::storage::Kind;
+use crate::title::Title;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+/// Callback data prefix of the buttons under a suggestion.
+const PREFIX: &str = "oner";
+
+/// What a button under a suggestion does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Watched,
+    Later,
+    Another,
+}
+
+impl Action {
+    const ALL: [Action; 3] = [Action::Watched, Action::Later, Action::Another];
+
+    fn key(self) -> &'static str {
+        match self {
+            Action::Watched => "izledim",
+            Action::Later => "sonra",
+            Action::Another => "baska",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Action::Watched => "✅ İzledim",
+            Action::Later => "📝 Sonra",
+            Action::Another => "🔄 Başka öner",
+        }
+    }
+}
+
+/// Decoded callback data of a suggestion button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonData {
+    pub action: Action,
+    pub kind: Kind,
+    pub title_id: u64,
+}
+
+impl ButtonData {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}:{}:{}:{:016x}",
+            PREFIX,
+            self.action.key(),
+            self.kind.key(),
+            self.title_id
+        )
+    }
+
+    pub fn parse(data: &str) -> Option<Self> {
+        let mut parts = data.split(':');
+        if parts.next()? != PREFIX {
+            return None;
+        }
+        let action = parts.next()?;
+        let data = ButtonData {
+            action: Action::ALL.into_iter().find(|a| a.key() == action)?,
+            kind: Kind::from_key(parts.next()?)?,
+            title_id: u64::from_str_radix(parts.next()?, 16).ok()?,
+        };
+        parts.next().is_none().then_some(data)
+    }
+}
+
+/// Telegram limits callback data to 64 bytes, too short for some titles, so
+/// buttons carry this hash instead: FNV-1a of the title, stable across
+/// restarts so old buttons keep working.
+pub fn title_id(title: &Title) -> u64 {
+    title
+        .to_string()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+pub fn suggestion_keyboard(kind: Kind, title: &Title) -> InlineKeyboardMarkup {
+    let title_id = title_id(title);
+    InlineKeyboardMarkup::new(vec![Action::ALL
+        .into_iter()
+        .map(|action| {
+            let data = ButtonData {
+                action,
+                kind,
+                title_id,
+            };
+            InlineKeyboardButton::callback(action.label(), data.encode())
+        })
+        .collect::<Vec<_>>()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callback_data_round_trips_and_fits() {
+        let title = Title::parse("Bir Zamanlar Anadolu'da Çok Uzun Bir Film Adı (2011)");
+        let data = ButtonData {
+            action: Action::Another,
+            kind: Kind::Series,
+            title_id: title_id(&title),
+        };
+        let encoded = data.encode();
+        assert!(encoded.len() <= 64);
+        assert_eq!(ButtonData::parse(&encoded), Some(data));
+        assert_eq!(ButtonData::parse("oner:izledim:film"), None);
+        assert_eq!(ButtonData::parse("baska:izledim:film:00"), None);
+    }
+}
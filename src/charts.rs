@@ -0,0 +1,317 @@
+use crate::review::YearReview;
+use crate::stats::{Progress, Stats};
+use log::error;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::register_font;
+use std::io;
+use std::sync::Once;
+
+pub const WIDTH: u32 = 800;
+pub const HEIGHT: u32 = 480;
+
+/// Embedded so charts render the same everywhere, including the slim Docker
+/// image that has no fonts installed.
+static FONT: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+const FONT_FAMILY: &str = "sans-serif";
+
+const WATCHED_COLOR: RGBColor = RGBColor(46, 139, 87);
+const TOTAL_COLOR: RGBColor = RGBColor(200, 215, 230);
+const LEFT_COLOR: RGBColor = RGBColor(220, 220, 220);
+/// Height of the colored band at the top of the year in review card.
+const CARD_HEADER: u32 = 90;
+
+/// A rendered chart and the caption it is sent with.
+pub struct Chart {
+    pub caption: String,
+    pub png: Vec<u8>,
+}
+
+/// Every chart the statistics have data for. Watches per month only show up
+/// once the history has dates in it.
+pub fn render_all(stats: &Stats) -> io::Result<Vec<Chart>> {
+    let mut charts = Vec::new();
+    if !stats.monthly.is_empty() {
+        charts.push(Chart {
+            caption: "🗓️ Aylara göre izlemeler".into(),
+            png: render(|area| draw_monthly(area, stats))?,
+        });
+    }
+    if !stats.decades.is_empty() {
+        charts.push(Chart {
+            caption: "📅 Yıllara göre filmler (izlenen / toplam)".into(),
+            png: render(|area| draw_decades(area, stats))?,
+        });
+    }
+    if stats.films.total + stats.series.total > 0 {
+        charts.push(Chart {
+            caption: "✅ Tamamlanma oranı".into(),
+            png: render(|area| draw_completion(area, stats))?,
+        });
+    }
+    Ok(charts)
+}
+
+/// The year in review as a shareable card.
+pub fn render_review_card(review: &YearReview) -> io::Result<Vec<u8>> {
+    render(|area| draw_review_card(area, review))
+}
+
+type DrawResult = Result<(), Box<dyn std::error::Error>>;
+
+fn render(
+    draw: impl FnOnce(&DrawingArea<BitMapBackend, Shift>) -> DrawResult,
+) -> io::Result<Vec<u8>> {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        if register_font(FONT_FAMILY, FontStyle::Normal, FONT).is_err() {
+            error!("The embedded chart font could not be loaded.");
+        }
+    });
+
+    let mut pixels = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let area = BitMapBackend::with_buffer(&mut pixels, (WIDTH, HEIGHT)).into_drawing_area();
+        (|| -> DrawResult {
+            area.fill(&WHITE)?;
+            draw(&area)?;
+            area.present()?;
+            Ok(())
+        })()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    }
+    encode_png(&pixels)
+}
+
+fn encode_png(pixels: &[u8]) -> io::Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, WIDTH, HEIGHT);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(pixels).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(png)
+}
+
+/// Bars over `labels`, one series per `(values, color)`, drawn in order so
+/// later series sit on top of earlier ones.
+fn draw_bars(
+    area: &DrawingArea<BitMapBackend, Shift>,
+    title: &str,
+    labels: &[String],
+    series: &[(Vec<usize>, RGBColor)],
+) -> DrawResult {
+    let max = series
+        .iter()
+        .flat_map(|(values, _)| values.iter().copied())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut chart = ChartBuilder::on(area)
+        .caption(title, (FONT_FAMILY, 26))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(40)
+        .build_cartesian_2d(
+            (0..labels.len() - 1).into_segmented(),
+            0..max + max / 10 + 1,
+        )?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(labels.len())
+        .x_label_formatter(&|x| match x {
+            SegmentValue::CenterOf(i) => labels.get(*i).cloned().unwrap_or_default(),
+            _ => String::new(),
+        })
+        .label_style((FONT_FAMILY, 14))
+        .draw()?;
+    for (values, color) in series {
+        chart.draw_series(
+            Histogram::vertical(&chart)
+                .style(color.filled())
+                .margin(6)
+                .data(values.iter().enumerate().map(|(i, &v)| (i, v))),
+        )?;
+    }
+    Ok(())
+}
+
+fn draw_monthly(area: &DrawingArea<BitMapBackend, Shift>, stats: &Stats) -> DrawResult {
+    let labels: Vec<String> = stats
+        .monthly
+        .iter()
+        .map(|(year, month, _)| format!("{:02}.{:02}", month, year % 100))
+        .collect();
+    let counts = stats.monthly.iter().map(|m| m.2).collect();
+    draw_bars(
+        area,
+        "Aylara göre izlemeler",
+        &labels,
+        &[(counts, WATCHED_COLOR)],
+    )
+}
+
+fn draw_decades(area: &DrawingArea<BitMapBackend, Shift>, stats: &Stats) -> DrawResult {
+    let labels: Vec<String> = stats.decades.keys().map(|d| format!("{}'ler", d)).collect();
+    let totals = stats.decades.values().map(|d| d.total).collect();
+    let watched = stats.decades.values().map(|d| d.watched).collect();
+    draw_bars(
+        area,
+        "Yıllara göre filmler",
+        &labels,
+        &[(totals, TOTAL_COLOR), (watched, WATCHED_COLOR)],
+    )
+}
+
+/// One pie per kind, side by side.
+fn draw_completion(area: &DrawingArea<BitMapBackend, Shift>, stats: &Stats) -> DrawResult {
+    let area = area
+        .margin(12, 0, 0, 0)
+        .titled("Tamamlanma oranı", (FONT_FAMILY, 26))?;
+    let halves = area.split_evenly((1, 2));
+    for (half, (name, progress)) in halves
+        .iter()
+        .zip([("Filmler", stats.films), ("Diziler", stats.series)])
+    {
+        draw_pie(half, name, progress)?;
+    }
+    Ok(())
+}
+
+fn draw_pie(
+    area: &DrawingArea<BitMapBackend, Shift>,
+    name: &str,
+    progress: Progress,
+) -> DrawResult {
+    let (width, height) = area.dim_in_pixel();
+    let radius = f64::from(width.min(height)) * 0.3;
+    area.draw(&Text::new(
+        format!("{}: {}/{}", name, progress.watched, progress.total),
+        (width as i32 / 2 - radius as i32, 10),
+        (FONT_FAMILY, 20),
+    ))?;
+    // Unlike other elements, a pie is placed in backend pixels rather than
+    // relative to the area it is drawn on.
+    let (left, top) = area.get_base_pixel();
+    let center = (left + width as i32 / 2, top + height as i32 / 2 + 10);
+    if progress.total == 0 {
+        return Ok(());
+    }
+    let sizes = [progress.watched as f64, progress.unwatched() as f64];
+    let colors = [WATCHED_COLOR, LEFT_COLOR];
+    let labels = ["İzlendi", "Kaldı"];
+    let mut pie = Pie::new(&center, &radius, &sizes, &colors, &labels);
+    pie.start_angle(-90.0);
+    pie.label_style((FONT_FAMILY, 16));
+    pie.percentages((FONT_FAMILY, 16).into_font().color(&BLACK));
+    area.draw(&pie)?;
+    Ok(())
+}
+
+fn draw_review_card(area: &DrawingArea<BitMapBackend, Shift>, review: &YearReview) -> DrawResult {
+    let (header, body) = area.split_vertically(CARD_HEADER);
+    header.fill(&WATCHED_COLOR)?;
+    header.draw(&Text::new(
+        format!("{} Yıl Özeti", review.year),
+        (32, 22),
+        (FONT_FAMILY, 40).into_font().color(&WHITE),
+    ))?;
+    for (i, line) in review.card_lines().iter().enumerate() {
+        body.draw(&Text::new(
+            line.as_str(),
+            (32, 20 + i as i32 * 32),
+            (FONT_FAMILY, 22),
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn decode(png: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let mut reader = png::Decoder::new(png).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        (info, pixels)
+    }
+
+    fn pixels_of(pixels: &[u8], color: RGBColor) -> usize {
+        pixels_in(pixels, color, 0..WIDTH)
+    }
+
+    fn pixels_in(pixels: &[u8], color: RGBColor, columns: std::ops::Range<u32>) -> usize {
+        pixels
+            .chunks(3)
+            .enumerate()
+            .filter(|(i, _)| columns.contains(&(*i as u32 % WIDTH)))
+            .filter(|(_, p)| *p == [color.0, color.1, color.2])
+            .count()
+    }
+
+    #[test]
+    fn charts_are_pngs_drawn_from_the_stats() {
+        let stats = Stats {
+            films: Progress {
+                total: 10,
+                watched: 4,
+            },
+            series: Progress {
+                total: 4,
+                watched: 3,
+            },
+            decades: BTreeMap::from([
+                (
+                    1990,
+                    Progress {
+                        total: 6,
+                        watched: 1,
+                    },
+                ),
+                (
+                    2000,
+                    Progress {
+                        total: 4,
+                        watched: 3,
+                    },
+                ),
+            ]),
+            ..Stats::default()
+        };
+        let charts = render_all(&stats).unwrap();
+        // No watch dates yet, so no monthly chart.
+        assert_eq!(charts.len(), 2);
+
+        let (info, pixels) = decode(&charts[0].png);
+        assert_eq!((info.width, info.height), (WIDTH, HEIGHT));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        let watched = pixels_of(&pixels, WATCHED_COLOR);
+        let total = pixels_of(&pixels, TOTAL_COLOR);
+        // Watched bars cover part of the total bars: 4 watched films, 6 not.
+        let ratio = total as f64 / watched as f64;
+        assert!((ratio - 1.5).abs() < 0.1, "bar ratio was {}", ratio);
+
+        // One pie per half: 4 of 10 films and 3 of 4 series watched.
+        let (_, pixels) = decode(&charts[1].png);
+        for (columns, expected) in [(0..WIDTH / 2, 0.4), (WIDTH / 2..WIDTH, 0.75)] {
+            let watched = pixels_in(&pixels, WATCHED_COLOR, columns.clone()) as f64;
+            let left = pixels_in(&pixels, LEFT_COLOR, columns) as f64;
+            let share = watched / (watched + left);
+            assert!(
+                (share - expected).abs() < 0.05,
+                "watched share was {}",
+                share
+            );
+        }
+
+        let with_months = Stats {
+            monthly: vec![(2026, 1, 2), (2026, 2, 0), (2026, 3, 5)],
+            ..stats
+        };
+        assert_eq!(render_all(&with_months).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::library::Library;
+use crate::namespace::Namespace;
+use crate::storage::Kind;
+use crate::title::Title;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+/// Bumped whenever a field of [`Record`] changes meaning or goes away.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// One title of an export, with the same fields in every format. The field
+/// names below are the schema; CSV uses them as the header row and JSON as
+/// object keys. Empty optional fields are blank in CSV and `null` in JSON.
+/// Importing a CSV or JSON export only takes back the titles, their tags
+/// and whether they were watched.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// `film` or `dizi`.
+    #[serde(rename = "tur")]
+    pub kind: String,
+    /// The catalogue line without tags, e.g. `Matilda (1996)`.
+    #[serde(rename = "ad")]
+    pub title: String,
+    #[serde(rename = "yil")]
+    pub year: Option<u16>,
+    #[serde(rename = "sezon")]
+    pub seasons: Option<u32>,
+    /// Comma separated, e.g. `korku, gerilim`.
+    #[serde(rename = "etiketler")]
+    pub tags: String,
+    /// `false` for watched titles that have since left the catalogue.
+    #[serde(rename = "katalogda")]
+    pub in_catalogue: bool,
+    #[serde(rename = "izlendi")]
+    pub watched: bool,
+    /// Times watched; watches from before dates were kept count once.
+    #[serde(rename = "izlenme")]
+    pub watches: usize,
+    /// RFC 3339 in UTC, e.g. `2026-03-15T19:30:00Z`.
+    #[serde(rename = "son_izleme")]
+    pub last_watched: Option<String>,
+    /// RFC 3339 in UTC; only known for titles added through the bot.
+    #[serde(rename = "eklendi")]
+    pub added: Option<String>,
+    /// 1 to 10.
+    #[serde(rename = "puan")]
+    pub score: Option<u8>,
+    #[serde(rename = "not")]
+    pub note: Option<String>,
+    /// `planlandi`, `izleniyor`, `beklemede`, `birakildi` or `tamamlandi`.
+    #[serde(rename = "durum")]
+    pub status: Option<String>,
+    /// Last watched episode of a series, e.g. `S02E04`.
+    #[serde(rename = "bolum")]
+    pub episode: Option<String>,
+}
+
+impl Record {
+    fn new(kind: Kind, title: &Title, in_catalogue: bool) -> Self {
+        Record {
+            kind: kind.key().to_string(),
+            title: title.to_string(),
+            year: title.year,
+            seasons: title.seasons,
+            tags: title.tags.join(", "),
+            in_catalogue,
+            ..Record::default()
+        }
+    }
+}
+
+/// The JSON document: the records plus the schema version they follow.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    #[serde(rename = "surum")]
+    pub version: u32,
+    #[serde(rename = "kayitlar")]
+    pub records: Vec<Record>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl Format {
+    pub fn parse(arg: &str) -> Option<Self> {
+        match arg.trim().to_lowercase().as_str() {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            "md" | "markdown" => Some(Format::Markdown),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Markdown => "md",
+        }
+    }
+
+    pub fn write(self, records: &[Record]) -> io::Result<Vec<u8>> {
+        match self {
+            Format::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                for record in records {
+                    writer.serialize(record).map_err(io::Error::other)?;
+                }
+                writer
+                    .into_inner()
+                    .map_err(|e| io::Error::other(e.to_string()))
+            }
+            Format::Json => {
+                let document = Document {
+                    version: SCHEMA_VERSION,
+                    records: records.to_vec(),
+                };
+                let mut json = serde_json::to_vec_pretty(&document)?;
+                json.push(b'\n');
+                Ok(json)
+            }
+            Format::Markdown => Ok(markdown(records).into_bytes()),
+        }
+    }
+}
+
+/// Every catalogue entry of both kinds, followed by watched titles that are
+/// no longer in the catalogue, with what `ns` knows about each.
+pub fn records(library: &Library, ns: &Namespace) -> io::Result<Vec<Record>> {
+    let history = library.history(ns)?;
+    let progress: HashMap<String, String> = library
+        .progress(ns)?
+        .into_iter()
+        .map(|(title, episode)| (title.to_string(), episode.to_string()))
+        .collect();
+
+    let mut records = Vec::new();
+    for kind in Kind::ALL {
+        let watched = library.watched(ns, kind)?;
+        let counts = library.watch_counts(ns, kind)?;
+        let added = library.added_dates(kind)?;
+        let ratings: HashMap<String, _> = library
+            .ratings(ns, kind)?
+            .into_iter()
+            .map(|(title, rating)| (title.to_string(), rating))
+            .collect();
+        let statuses: HashMap<String, _> = library
+            .statuses(ns, kind)?
+            .into_iter()
+            .map(|(title, status)| (title.to_string(), status))
+            .collect();
+        let mut last_watched: HashMap<&str, DateTime<Utc>> = HashMap::new();
+        for event in history.iter().filter(|e| e.kind == kind) {
+            last_watched.insert(&event.title, event.at);
+        }
+
+        let catalogue = library.catalogue(kind);
+        let listed: HashSet<String> = catalogue.iter().map(|t| t.to_string()).collect();
+        let mut gone: Vec<&String> = watched.iter().filter(|t| !listed.contains(*t)).collect();
+        gone.sort();
+        let titles = catalogue
+            .iter()
+            .map(|t| (t.clone(), true))
+            .chain(gone.into_iter().map(|t| (Title::parse(t), false)));
+
+        for (title, in_catalogue) in titles {
+            let mut record = Record::new(kind, &title, in_catalogue);
+            let key = record.title.clone();
+            record.watched = watched.contains(&key);
+            record.watches = counts.get(&key).copied().unwrap_or(0);
+            record.last_watched = last_watched.get(key.as_str()).map(timestamp);
+            record.added = added.get(&key).map(timestamp);
+            if let Some(rating) = ratings.get(&key) {
+                record.score = Some(rating.score);
+                record.note = rating.note.clone();
+            }
+            record.status = statuses.get(&key).map(|s| s.key().to_string());
+            record.episode = progress.get(&key).cloned();
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+fn timestamp(at: &DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// A table per kind, meant for reading.
+fn markdown(records: &[Record]) -> String {
+    let mut out = String::from("# Film ve Dizi Arşivi\n");
+    for kind in Kind::ALL {
+        let rows: Vec<&Record> = records.iter().filter(|r| r.kind == kind.key()).collect();
+        out.push_str(&format!(
+            "\n## {} {}\n\n",
+            kind.emoji(),
+            kind.title_plural()
+        ));
+        if rows.is_empty() {
+            out.push_str("_Kayıt yok._\n");
+            continue;
+        }
+        out.push_str("| Ad | Etiketler | İzlendi | Puan | Not | Durum | Son izleme |\n");
+        out.push_str("|---|---|---|---|---|---|---|\n");
+        for record in rows {
+            let mut name = cell(&record.title);
+            if !record.in_catalogue {
+                name.push_str(" *(katalogda yok)*");
+            }
+            let watched = match (record.watched, record.watches) {
+                (false, _) => String::new(),
+                (true, 0 | 1) => "✅".to_string(),
+                (true, n) => format!("✅ ×{}", n),
+            };
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} |\n",
+                name,
+                cell(&record.tags),
+                watched,
+                record
+                    .score
+                    .map(|s| format!("{}/10", s))
+                    .unwrap_or_default(),
+                cell(record.note.as_deref().unwrap_or("")),
+                record.status.as_deref().unwrap_or(""),
+                record
+                    .last_watched
+                    .as_deref()
+                    .and_then(|at| at.get(..10))
+                    .unwrap_or(""),
+            ));
+        }
+    }
+    out
+}
+
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Actor;
+    use crate::rating::Rating;
+
+    #[test]
+    fn exports_round_trip_through_csv_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = Library::in_dir(dir.path());
+        let ns = Namespace::User(7);
+        let actor = Actor { ns, user: None };
+        library
+            .add(&actor, Kind::Film, "Matilda (1996) | komedi, aile")
+            .unwrap();
+        library.add(&actor, Kind::Film, "Alien (1979)").unwrap();
+        library.add(&actor, Kind::Series, "Dark (3 Sezon)").unwrap();
+        library
+            .mark_watched(&actor, Kind::Film, "Matilda (1996)")
+            .unwrap();
+        library
+            .mark_watched(&actor, Kind::Film, "Silinen (2001)")
+            .unwrap();
+        library
+            .rate(
+                &actor,
+                Kind::Film,
+                &Title::parse("Matilda (1996)"),
+                Rating::new(9, Some("Çok güzel, \"klasik\"".into())).unwrap(),
+            )
+            .unwrap();
+
+        let records = records(&library, &ns).unwrap();
+        let names: Vec<&str> = records.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Matilda (1996)",
+                "Alien (1979)",
+                "Silinen (2001)",
+                "Dark (3 Sezon)"
+            ]
+        );
+        let matilda = &records[0];
+        assert_eq!(matilda.tags, "komedi, aile");
+        assert!(matilda.watched && matilda.in_catalogue);
+        assert_eq!((matilda.watches, matilda.score), (1, Some(9)));
+        assert!(matilda.last_watched.as_deref().unwrap().ends_with('Z'));
+        assert!(!records[2].in_catalogue);
+        assert_eq!(records[3].seasons, Some(3));
+
+        let csv = Format::Csv.write(&records).unwrap();
+        let header = String::from_utf8(csv.clone()).unwrap();
+        assert!(header.starts_with(
+            "tur,ad,yil,sezon,etiketler,katalogda,izlendi,izlenme,son_izleme,eklendi,puan,not,durum,bolum\n"
+        ));
+        let read: Vec<Record> = csv::Reader::from_reader(csv.as_slice())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, records);
+
+        let json = Format::Json.write(&records).unwrap();
+        let document: Document = serde_json::from_slice(&json).unwrap();
+        assert_eq!(document.version, SCHEMA_VERSION);
+        assert_eq!(document.records, records);
+
+        let md = String::from_utf8(Format::Markdown.write(&records).unwrap()).unwrap();
+        assert!(md.contains("| Matilda (1996) | komedi, aile | ✅ | 9/10 |"));
+        assert!(md.contains("Silinen (2001) *(katalogda yok)*"));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::title::Title;
+use std::fmt;
+
+/// Restricts suggestions to titles carrying every given tag and, for films,
+/// released within a year range: `korku 2010-2020`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TitleFilter {
+    pub tags: Vec<String>,
+    pub years: Option<(u16, u16)>,
+}
+
+impl TitleFilter {
+    /// Words that look like a year or year range narrow the years; any other
+    /// word is a tag.
+    pub fn parse(arg: &str) -> Result<Self, String> {
+        let mut filter = TitleFilter::default();
+        for word in arg.split_whitespace() {
+            if let Some(years) = parse_years(word)? {
+                if filter.years.is_some() {
+                    return Err("yalnızca bir yıl aralığı verebilirsin".into());
+                }
+                filter.years = Some(years);
+            } else {
+                let tag = word.to_lowercase();
+                if !filter.tags.contains(&tag) {
+                    filter.tags.push(tag);
+                }
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.years.is_none()
+    }
+
+    pub fn matches(&self, title: &Title) -> bool {
+        let in_years = match self.years {
+            Some((from, to)) => title.year.is_some_and(|y| (from..=to).contains(&y)),
+            None => true,
+        };
+        in_years && self.tags.iter().all(|tag| title.has_tag(tag))
+    }
+}
+
+/// `Ok(None)` for a word that is not a year at all.
+pub fn parse_years(word: &str) -> Result<Option<(u16, u16)>, String> {
+    let is_year = |s: &str| s.len() == 4 && s.bytes().all(|b| b.is_ascii_digit());
+    let (from, to) = match word.split_once('-') {
+        Some((from, to)) if is_year(from) && is_year(to) => (from, to),
+        None if is_year(word) => (word, word),
+        _ => return Ok(None),
+    };
+    let (from, to): (u16, u16) = (from.parse().unwrap(), to.parse().unwrap());
+    if from > to {
+        return Err(format!("'{}' geçerli bir yıl aralığı değil", word));
+    }
+    Ok(Some((from, to)))
+}
+
+impl fmt::Display for TitleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.tags.clone();
+        match self.years {
+            Some((from, to)) if from == to => parts.push(from.to_string()),
+            Some((from, to)) => parts.push(format!("{}-{}", from, to)),
+            None => {}
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// How a suggestion was asked for: its filter and whether titles being
+/// watched come first (`devam`). It is written under every suggestion and
+/// read back when "Başka öner" is pressed, as callback data has no room for
+/// it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Wanted {
+    pub filter: TitleFilter,
+    pub prefer_in_progress: bool,
+}
+
+impl Wanted {
+    const LABEL: &'static str = "🔍 Filtre: ";
+
+    /// The line shown under a suggestion; `None` when nothing was asked for.
+    pub fn caption(&self) -> Option<String> {
+        let mut words = self.filter.tags.clone();
+        match self.filter.years {
+            Some((from, to)) if from == to => words.push(from.to_string()),
+            Some((from, to)) => words.push(format!("{}-{}", from, to)),
+            None => {}
+        }
+        if self.prefer_in_progress {
+            words.push("devam".into());
+        }
+        (!words.is_empty()).then(|| format!("{}{}", Self::LABEL, words.join(" ")))
+    }
+
+    /// Reads back [`Wanted::caption`] from a suggestion's text.
+    pub fn from_text(text: &str) -> Self {
+        let Some(words) = text.lines().find_map(|line| line.strip_prefix(Self::LABEL)) else {
+            return Wanted::default();
+        };
+        let (devam, rest): (Vec<&str>, Vec<&str>) =
+            words.split_whitespace().partition(|w| *w == "devam");
+        Wanted {
+            filter: TitleFilter::parse(&rest.join(" ")).unwrap_or_default(),
+            prefer_in_progress: !devam.is_empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_tag_and_year_range() {
+        let filter = TitleFilter::parse("Korku 2010-2020").unwrap();
+        assert_eq!(filter.tags, vec!["korku"]);
+        assert_eq!(filter.years, Some((2010, 2020)));
+
+        assert!(filter.matches(&Title::parse("It Follows (2014) | korku, gerilim")));
+        assert!(!filter.matches(&Title::parse("It (1990) | korku")));
+        assert!(!filter.matches(&Title::parse("Inception (2010) | bilim kurgu")));
+        assert!(!filter.matches(&Title::parse("Dark (3 Sezon) | korku")));
+        assert!(TitleFilter::parse("2020-2010").is_err());
+        assert!(TitleFilter::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn suggestions_carry_what_was_asked_for() {
+        let wanted = Wanted {
+            filter: TitleFilter::parse("korku gerilim 2010-2020").unwrap(),
+            prefer_in_progress: true,
+        };
+        let text = format!(
+            "🎬 Film Önerisi: It Follows (2014)\n{}",
+            wanted.caption().unwrap()
+        );
+        assert!(text.ends_with("🔍 Filtre: korku gerilim 2010-2020 devam"));
+        assert_eq!(Wanted::from_text(&text), wanted);
+
+        assert_eq!(Wanted::default().caption(), None);
+        assert_eq!(
+            Wanted::from_text("🎬 Film Önerisi: It (1990)"),
+            Wanted::default()
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use crate::storage::Kind;
+use chrono::{DateTime, Local, Utc};
+use std::collections::HashMap;
+
+/// One time a title was watched. The watched lists only say *whether*
+/// something was watched; these say when, and how often.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchEvent {
+    pub kind: Kind,
+    pub title: String,
+    pub at: DateTime<Utc>,
+}
+
+impl WatchEvent {
+    pub fn now(kind: Kind, title: &str) -> Self {
+        WatchEvent {
+            kind,
+            title: title.to_string(),
+            at: Utc::now(),
+        }
+    }
+
+    pub fn local_date(&self) -> String {
+        self.at
+            .with_timezone(&Local)
+            .format("%d.%m.%Y %H:%M")
+            .to_string()
+    }
+}
+
+/// How many times each title of `kind` shows up in `events`.
+pub fn watch_counts(events: &[WatchEvent], kind: Kind) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for event in events.iter().filter(|e| e.kind == kind) {
+        *counts.entry(event.title.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn counts_rewatches_per_kind_and_dates_them_locally() {
+        let at = Local
+            .with_ymd_and_hms(2026, 3, 15, 19, 30, 0)
+            .unwrap()
+            .to_utc();
+        let watch = |kind, title: &str| WatchEvent {
+            kind,
+            title: title.into(),
+            at,
+        };
+        let events = vec![
+            watch(Kind::Film, "Matilda (1996)"),
+            watch(Kind::Series, "Matilda (1996)"),
+            watch(Kind::Film, "Matilda (1996)"),
+            watch(Kind::Film, "Alien (1979)"),
+        ];
+        let films = watch_counts(&events, Kind::Film);
+        assert_eq!(films.len(), 2);
+        assert_eq!((films["Matilda (1996)"], films["Alien (1979)"]), (2, 1));
+        assert_eq!(watch_counts(&events, Kind::Series)["Matilda (1996)"], 1);
+        assert_eq!(events[0].local_date(), "15.03.2026 19:30");
+    }
+}
@@ -0,0 +1,407 @@
+use crate::journal::Actor;
+use crate::library::{Library, Lookup};
+use crate::normalize::fold;
+use crate::storage::Kind;
+use crate::title::Title;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io;
+
+/// Larger uploads are turned down before they are downloaded.
+pub const MAX_IMPORT_BYTES: u32 = 1024 * 1024;
+
+/// File extensions [`parse`] can read.
+pub const EXTENSIONS: [&str; 3] = ["txt", "csv", "json"];
+
+/// Whether an upload is meant to be imported: a file [`parse`] can read,
+/// sent in a private chat, or in a group with a caption starting with
+/// `/ice_aktar`.
+pub fn is_requested(file_name: &str, caption: &str, private: bool) -> bool {
+    if !EXTENSIONS.contains(&extension(file_name).as_str()) {
+        return false;
+    }
+    private
+        || caption
+            .split_whitespace()
+            .next()
+            .and_then(|command| command.split('@').next())
+            .is_some_and(|command| command.eq_ignore_ascii_case("/ice_aktar"))
+}
+
+fn extension(file_name: &str) -> String {
+    file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Where the lines of an upload go, read from the caption sent with it:
+/// `film` or `dizi` for the kind, and `izlenen` for the watched list instead
+/// of the catalogue. CSV and JSON rows can name their own kind and state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Target {
+    pub kind: Option<Kind>,
+    pub watched: bool,
+}
+
+impl Target {
+    pub fn parse(caption: &str) -> Self {
+        let mut target = Target::default();
+        for word in caption.split_whitespace() {
+            match fold(word).as_str() {
+                "izlenen" | "izlendi" | "izledim" => target.watched = true,
+                word => {
+                    if let Some(kind) = Kind::from_key(word) {
+                        target.kind = Some(kind);
+                    }
+                }
+            }
+        }
+        target
+    }
+}
+
+/// One title read from an upload, with where it should end up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Item {
+    /// 1-based line of a text or CSV file, or position in a JSON list.
+    pub line: usize,
+    pub kind: Kind,
+    /// A catalogue line, tags included.
+    pub title: String,
+    pub catalogue: bool,
+    pub watched: bool,
+    /// An export row of a watched title that had left the catalogue; it goes
+    /// back into the watched list without being looked up.
+    pub unlisted: bool,
+}
+
+/// A line that could not be read, and why.
+pub type Invalid = (usize, String);
+
+/// The columns of an export that matter for importing. Only `ad` is
+/// required; the others, such as ratings, notes and dates, are ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Row {
+    #[serde(rename = "tur")]
+    kind: Option<String>,
+    #[serde(rename = "ad")]
+    title: String,
+    #[serde(rename = "etiketler")]
+    tags: String,
+    #[serde(rename = "katalogda")]
+    in_catalogue: Option<bool>,
+    #[serde(rename = "izlendi")]
+    watched: Option<bool>,
+}
+
+impl Row {
+    fn item(self, line: usize, target: Target) -> Result<Item, Invalid> {
+        let kind = match self.kind.as_deref().map(fold) {
+            Some(key) if !key.is_empty() => {
+                Kind::from_key(&key).ok_or_else(|| (line, format!("'{}' bir tür değil", key)))?
+            }
+            _ => target
+                .kind
+                .ok_or_else(|| (line, "film mi dizi mi belli değil".to_string()))?,
+        };
+        let title = if self.tags.trim().is_empty() {
+            self.title
+        } else {
+            format!("{} | {}", self.title, self.tags)
+        };
+        let watched = self.watched.unwrap_or(false) || target.watched;
+        let mut item = Item::new(
+            line,
+            kind,
+            &title,
+            self.in_catalogue.unwrap_or(!target.watched),
+            watched,
+        )?;
+        item.unlisted = self.in_catalogue == Some(false);
+        Ok(item)
+    }
+}
+
+impl Item {
+    fn new(
+        line: usize,
+        kind: Kind,
+        title: &str,
+        catalogue: bool,
+        watched: bool,
+    ) -> Result<Self, Invalid> {
+        let title = Title::parse(title.trim());
+        if title.name.is_empty() {
+            return Err((line, "ad boş".into()));
+        }
+        Ok(Item {
+            line,
+            kind,
+            title: title.line(),
+            catalogue,
+            watched,
+            unlisted: false,
+        })
+    }
+}
+
+/// Reads an upload by its extension: `.txt` with one catalogue line per line
+/// (empty lines and `#` comments are skipped), `.csv` with a header row, or
+/// `.json` holding a list of lines or rows, or an export document. Whole-file
+/// problems are an `Err`; bad lines are returned next to the good ones.
+pub fn parse(
+    file_name: &str,
+    contents: &[u8],
+    target: Target,
+) -> Result<(Vec<Item>, Vec<Invalid>), String> {
+    let text = std::str::from_utf8(contents).map_err(|_| "dosya UTF-8 değil".to_string())?;
+    let text = text.trim_start_matches('\u{feff}');
+    let results = match extension(file_name).as_str() {
+        "txt" => parse_text(text, target)?,
+        "csv" => parse_csv(text, target)?,
+        "json" => parse_json(text, target)?,
+        _ => return Err("yalnızca .txt, .csv ve .json dosyalarını okuyabilirim".into()),
+    };
+    Ok(split(results))
+}
+
+fn parse_text(text: &str, target: Target) -> Result<Vec<Result<Item, Invalid>>, String> {
+    let kind = target
+        .kind
+        .ok_or("metin dosyalarında açıklamaya `film` veya `dizi` yazmalısın")?;
+    Ok(text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| Item::new(i + 1, kind, line, !target.watched, target.watched))
+        .collect())
+}
+
+fn parse_csv(text: &str, target: Target) -> Result<Vec<Result<Item, Invalid>>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?;
+    if !headers.iter().any(|h| h.trim() == "ad") {
+        return Err("CSV dosyasında `ad` sütunu olmalı".into());
+    }
+    let headers = headers.clone();
+    Ok(reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            // Quoted fields can span lines, so the row count is only a
+            // fallback for the reader's own position.
+            let line = match &record {
+                Ok(record) => record.position().map(|p| p.line() as usize),
+                Err(e) => e.position().map(|p| p.line() as usize),
+            }
+            .unwrap_or(i + 2);
+            record
+                .and_then(|record| record.deserialize::<Row>(Some(&headers)))
+                .map_err(|e| (line, e.to_string()))
+                .and_then(|row| row.item(line, target))
+        })
+        .collect())
+}
+
+fn parse_json(text: &str, target: Target) -> Result<Vec<Result<Item, Invalid>>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("JSON okunamadı: {}", e))?;
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut document) => match document.remove("kayitlar") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => return Err("JSON'da `kayitlar` listesi yok".into()),
+        },
+        _ => return Err("JSON bir liste veya dışa aktarılmış bir belge olmalı".into()),
+    };
+    Ok(items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| match item {
+            serde_json::Value::String(line) => {
+                let kind = target
+                    .kind
+                    .ok_or_else(|| (i + 1, "film mi dizi mi belli değil".to_string()))?;
+                Item::new(i + 1, kind, &line, !target.watched, target.watched)
+            }
+            item => serde_json::from_value::<Row>(item)
+                .map_err(|e| (i + 1, e.to_string()))
+                .and_then(|row| row.item(i + 1, target)),
+        })
+        .collect())
+}
+
+fn split(results: Vec<Result<Item, Invalid>>) -> (Vec<Item>, Vec<Invalid>) {
+    let mut items = Vec::new();
+    let mut invalid = Vec::new();
+    for result in results {
+        match result {
+            Ok(item) => items.push(item),
+            Err(bad) => invalid.push(bad),
+        }
+    }
+    (items, invalid)
+}
+
+/// What an import changed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub added: Vec<String>,
+    /// Already in the catalogue or watched list, whatever the case or
+    /// diacritics.
+    pub skipped: Vec<String>,
+    pub invalid: Vec<Invalid>,
+}
+
+/// Adds the items the way `/film_ekle` and `/izlenen_film_ekle` would, all
+/// written at once and journaled as one change, so a single `/geri_al`
+/// takes the whole import back. Titles for the watched list have to be in
+/// the catalogue, unless an export row says they left it.
+pub fn apply(library: &Library, actor: &Actor, items: Vec<Item>) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+    let mut lines: Vec<(Kind, String)> = Vec::new();
+    let mut watched: Vec<(Kind, String)> = Vec::new();
+    // Where each kept item's line and watched title went, if anywhere.
+    let mut kept: Vec<(String, Option<usize>, Option<usize>)> = Vec::new();
+    let mut known: HashMap<Kind, Vec<Title>> = Kind::ALL
+        .into_iter()
+        .map(|kind| (kind, library.catalogue(kind)))
+        .collect();
+    for item in items {
+        let title = Title::parse(&item.title);
+        let line = item.catalogue.then(|| {
+            lines.push((item.kind, item.title.clone()));
+            known.entry(item.kind).or_default().push(title.clone());
+            lines.len() - 1
+        });
+        let mut marked = None;
+        if item.watched {
+            let key = if item.catalogue {
+                known[&item.kind]
+                    .iter()
+                    .find(|t| t.same_as(&title))
+                    .map(|t| t.to_string())
+            } else {
+                match library.lookup(item.kind, &title.to_string()) {
+                    Lookup::Found(found) => Some(found.to_string()),
+                    _ if item.unlisted => Some(title.to_string()),
+                    _ => None,
+                }
+            };
+            let Some(key) = key else {
+                summary.invalid.push((
+                    item.line,
+                    format!("'{}' {} kataloğunda yok", title, item.kind.key()),
+                ));
+                continue;
+            };
+            watched.push((item.kind, key));
+            marked = Some(watched.len() - 1);
+        }
+        kept.push((item.title, line, marked));
+    }
+
+    let (added, marked) = library.add_batch(actor, &lines, &watched)?;
+    for (title, line, watch) in kept {
+        if line.is_some_and(|i| added[i]) || watch.is_some_and(|i| marked[i]) {
+            summary.added.push(title);
+        } else {
+            summary.skipped.push(title);
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespace::Namespace;
+
+    #[test]
+    fn only_readable_files_sent_on_purpose_are_imported() {
+        assert!(is_requested("liste.TXT", "", true));
+        assert!(!is_requested("foto.jpg", "/ice_aktar", true));
+        assert!(!is_requested("liste.csv", "film", false));
+        assert!(is_requested(
+            "liste.csv",
+            "/ice_aktar@arsiv_bot film",
+            false
+        ));
+        assert!(!is_requested("liste", "/ice_aktar", false));
+    }
+
+    #[test]
+    fn imports_text_csv_and_json_without_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = Library::in_dir(dir.path());
+        let ns = Namespace::User(9);
+        let actor = Actor { ns, user: None };
+        library.add(&actor, Kind::Film, "Matilda (1996)").unwrap();
+
+        let films = Target::parse("Film");
+        let text = "# izleme listem\nmatilda (1996)\n\nAlien (1979) | bilimkurgu\nAlien (1979)\n  | sadece etiket\n";
+        let (items, invalid) = parse("liste.TXT", text.as_bytes(), films).unwrap();
+        assert_eq!(invalid, vec![(6, "ad boş".to_string())]);
+        let summary = apply(&library, &actor, items).unwrap();
+        assert_eq!(summary.added, vec!["Alien (1979) | bilimkurgu"]);
+        assert_eq!(summary.skipped, vec!["matilda (1996)", "Alien (1979)"]);
+        assert!(parse("liste.txt", text.as_bytes(), Target::default()).is_err());
+
+        let csv = "tur,ad,etiketler,izlendi,puan\ndizi,Dark (3 Sezon),gerilim,true,9\nkitap,Dune,,,\nfilm,Alien (1979),,true,\n";
+        let (items, invalid) = parse("arsiv.csv", csv.as_bytes(), Target::default()).unwrap();
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].0, 3);
+        let summary = apply(&library, &actor, items).unwrap();
+        assert_eq!(summary.added.len(), 2);
+        assert!(library
+            .watched(&ns, Kind::Series)
+            .unwrap()
+            .contains("Dark (3 Sezon)"));
+        assert!(library
+            .watched(&ns, Kind::Film)
+            .unwrap()
+            .contains("Alien (1979)"));
+
+        let json = r#"{"surum": 1, "kayitlar": ["Matilda (1996)", "Yok (2000)", {"tur": "film", "ad": "Gitti (2001)", "katalogda": false, "izlendi": true}, 5]}"#;
+        let watched = Target::parse("izlenen film");
+        let (items, invalid) = parse("a.json", json.as_bytes(), watched).unwrap();
+        assert_eq!(invalid.len(), 1);
+        let summary = apply(&library, &actor, items).unwrap();
+        assert_eq!(summary.added, vec!["Matilda (1996)", "Gitti (2001)"]);
+        assert_eq!(
+            summary.invalid,
+            vec![(2, "'Yok (2000)' film kataloğunda yok".to_string())]
+        );
+        assert!(!library
+            .catalogue(Kind::Film)
+            .iter()
+            .any(|t| t.name == "Gitti"));
+        assert!(library
+            .watched(&ns, Kind::Film)
+            .unwrap()
+            .contains("Matilda (1996)"));
+
+        let journal = library.journal(&ns).unwrap();
+        let imports: Vec<usize> = crate::journal::changes(&journal).map(|c| c.len()).collect();
+        assert_eq!(imports, vec![1, 1, 3, 2]);
+        assert_eq!(library.undo(&actor, 1).unwrap().len(), 2);
+        assert_eq!(library.undo(&actor, 1).unwrap().len(), 3);
+        assert!(!library
+            .watched(&ns, Kind::Film)
+            .unwrap()
+            .contains("Alien (1979)"));
+        assert!(library.watched(&ns, Kind::Series).unwrap().is_empty());
+        assert!(!library
+            .catalogue(Kind::Series)
+            .iter()
+            .any(|t| t.name == "Dark"));
+        assert!(library
+            .catalogue(Kind::Film)
+            .iter()
+            .any(|t| t.name == "Alien"));
+    }
+}
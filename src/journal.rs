@@ -0,0 +1,300 @@
+use crate::namespace::Namespace;
+use crate::progress::Episode;
+use crate::rating::Rating;
+use crate::status::Status;
+use crate::storage::Kind;
+use chrono::{DateTime, Local, Utc};
+use std::collections::HashSet;
+use teloxide::types::{CallbackQuery, Message};
+
+/// Who made a change and whose lists it went to. In a group sharing one
+/// list these are different people's business, so both are journaled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Actor {
+    pub ns: Namespace,
+    pub user: Option<u64>,
+}
+
+impl Actor {
+    pub fn from_message(msg: &Message) -> Self {
+        Actor {
+            ns: Namespace::from_message(msg),
+            user: msg.from.as_ref().map(|u| u.id.0),
+        }
+    }
+
+    pub fn from_callback(query: &CallbackQuery) -> Option<Self> {
+        Some(Actor {
+            ns: Namespace::from_callback(query)?,
+            user: Some(query.from.id.0),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Added,
+    Watched,
+    Removed,
+    Unwatched,
+    Renamed,
+    Tagged,
+    StatusSet,
+    Rated,
+    EpisodeRecorded,
+    SeasonFinished,
+    /// Reverted the entry with this id.
+    Undone(u64),
+}
+
+impl Action {
+    pub fn key(self) -> String {
+        match self {
+            Action::Added => "ekle".into(),
+            Action::Watched => "izle".into(),
+            Action::Removed => "sil".into(),
+            Action::Unwatched => "izlenmedi".into(),
+            Action::Renamed => "adlandir".into(),
+            Action::Tagged => "etiket".into(),
+            Action::StatusSet => "durum".into(),
+            Action::Rated => "puan".into(),
+            Action::EpisodeRecorded => "bolum".into(),
+            Action::SeasonFinished => "sezon".into(),
+            Action::Undone(id) => format!("geri_al:{}", id),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "ekle" => Some(Action::Added),
+            "izle" => Some(Action::Watched),
+            "sil" => Some(Action::Removed),
+            "izlenmedi" => Some(Action::Unwatched),
+            "adlandir" => Some(Action::Renamed),
+            "etiket" => Some(Action::Tagged),
+            "durum" => Some(Action::StatusSet),
+            "puan" => Some(Action::Rated),
+            "bolum" => Some(Action::EpisodeRecorded),
+            "sezon" => Some(Action::SeasonFinished),
+            _ => Some(Action::Undone(key.strip_prefix("geri_al:")?.parse().ok()?)),
+        }
+    }
+}
+
+/// What a title looked like in the namespace before a change, which is
+/// what undoing the change puts back.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Before {
+    pub watched: bool,
+    pub status: Option<Status>,
+    pub episode: Option<Episode>,
+    pub rating: Option<Rating>,
+    pub added_at: Option<DateTime<Utc>>,
+    /// The catalogue line, tags included, before a rename or new tags.
+    pub line: Option<String>,
+    /// Watches dropped when the title was taken off the watched list.
+    pub history: Vec<DateTime<Utc>>,
+}
+
+/// One line of a namespace's journal. `title` is the catalogue line for
+/// additions, removals and renames, so tags come back on undo, and the
+/// watched-list key otherwise. Renaming a title rewrites it in every entry,
+/// so older entries can still be undone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub id: u64,
+    pub at: DateTime<Utc>,
+    pub user: Option<u64>,
+    pub action: Action,
+    pub kind: Kind,
+    pub title: String,
+    pub before: Before,
+    /// Id of the first entry of the import this change was part of. An
+    /// import is listed and undone as one change.
+    pub batch: Option<u64>,
+}
+
+impl Entry {
+    /// Follows a rename of the title, the way storage rewrites stored
+    /// entries.
+    pub fn retitle(&mut self, old: &str, new: &str) {
+        if let Some(title) = renamed_title(&self.title, old, new) {
+            self.title = title;
+        }
+    }
+
+    pub fn local_date(&self) -> String {
+        self.at
+            .with_timezone(&Local)
+            .format("%d.%m.%Y %H:%M")
+            .to_string()
+    }
+
+    pub fn describe(&self) -> String {
+        let what = match self.action {
+            Action::Added => "kataloğa eklendi".to_string(),
+            Action::Watched if self.before.watched => "tekrar izlendi".to_string(),
+            Action::Watched => "izlenenlere eklendi".to_string(),
+            Action::Removed => "katalogdan silindi".to_string(),
+            Action::Unwatched => "izlenenlerden çıkarıldı".to_string(),
+            Action::Renamed => {
+                let old = self.before.line.as_deref().unwrap_or_default();
+                return format!(
+                    "'{}' {} '{}' olarak yeniden adlandırıldı",
+                    old,
+                    self.kind.possessive(),
+                    self.title
+                );
+            }
+            Action::Tagged => "etiketleri değiştirildi".to_string(),
+            Action::StatusSet => "durumu değiştirildi".to_string(),
+            Action::Rated => "puanlandı".to_string(),
+            Action::EpisodeRecorded => "izlenen bölümü kaydedildi".to_string(),
+            Action::SeasonFinished => "sezonu bitirildi".to_string(),
+            Action::Undone(id) => format!("için #{} geri alındı", id),
+        };
+        format!("'{}' {} {}", self.title, self.kind.possessive(), what)
+    }
+}
+
+/// `title` with `old` renamed to `new`, if it is `old` or `old` followed by
+/// tags; `None` for any other title.
+pub fn renamed_title(title: &str, old: &str, new: &str) -> Option<String> {
+    let tags = title.strip_prefix(old)?;
+    (tags.is_empty() || tags.starts_with(" | ")).then(|| format!("{}{}", new, tags))
+}
+
+/// Watch times as stored in a journal column: Unix seconds separated by
+/// commas.
+pub fn encode_times(times: &[DateTime<Utc>]) -> String {
+    times
+        .iter()
+        .map(|at| at.timestamp().to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn decode_times(text: &str) -> Vec<DateTime<Utc>> {
+    text.split(',')
+        .filter_map(|secs| DateTime::from_timestamp(secs.trim().parse().ok()?, 0))
+        .collect()
+}
+
+/// Splits entries into what is shown and undone as one change: a single
+/// entry, or every entry of one import.
+pub fn changes(entries: &[Entry]) -> impl DoubleEndedIterator<Item = &[Entry]> {
+    entries.chunk_by(|a, b| a.batch.is_some() && a.batch == b.batch)
+}
+
+/// One line about a change; an import is summed up rather than listed.
+pub fn describe(change: &[Entry]) -> String {
+    match change {
+        [entry] => entry.describe(),
+        [first, ..] if matches!(first.action, Action::Undone(_)) => {
+            format!("içe aktarılan {} değişiklik geri alındı", change.len())
+        }
+        _ => format!("{} değişiklik içe aktarıldı", change.len()),
+    }
+}
+
+/// The most recent changes by `user` that can still be undone, newest
+/// first: neither undo entries themselves nor entries an undo already
+/// reverted. An import counts as one change but brings all its entries.
+pub fn undoable(entries: &[Entry], user: Option<u64>, count: usize) -> Vec<&Entry> {
+    let undone: HashSet<u64> = entries
+        .iter()
+        .filter_map(|e| match e.action {
+            Action::Undone(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+    let mut targets: Vec<&Entry> = Vec::new();
+    let mut changes = 0;
+    for entry in entries
+        .iter()
+        .rev()
+        .filter(|e| e.user == user)
+        .filter(|e| !matches!(e.action, Action::Undone(_)) && !undone.contains(&e.id))
+    {
+        let same_import =
+            entry.batch.is_some() && targets.last().is_some_and(|last| last.batch == entry.batch);
+        if !same_import {
+            if changes == count {
+                break;
+            }
+            changes += 1;
+        }
+        targets.push(entry);
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, action: Action) -> Entry {
+        Entry {
+            id,
+            at: Utc::now(),
+            user: None,
+            action,
+            kind: Kind::Film,
+            title: format!("Film {} (2000)", id),
+            before: Before::default(),
+            batch: None,
+        }
+    }
+
+    #[test]
+    fn undone_entries_are_skipped() {
+        let entries = vec![
+            entry(1, Action::Added),
+            entry(2, Action::Watched),
+            entry(3, Action::Removed),
+            entry(4, Action::Undone(3)),
+        ];
+        let ids: Vec<u64> = undoable(&entries, None, 5).iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+
+        let mut imported = vec![entry(5, Action::Added)];
+        for id in 6..9 {
+            imported.push(Entry {
+                batch: Some(6),
+                ..entry(id, Action::Added)
+            });
+        }
+        let ids: Vec<u64> = undoable(&imported, None, 1).iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![8, 7, 6]);
+        let lines: Vec<String> = changes(&imported).map(describe).collect();
+        assert_eq!(lines[1], "3 değişiklik içe aktarıldı");
+        assert_eq!(
+            Action::from_key(&Action::Undone(3).key()),
+            Some(Action::Undone(3))
+        );
+        assert_eq!(Action::from_key("geri_al:x"), None);
+        for action in [
+            Action::Unwatched,
+            Action::Renamed,
+            Action::Tagged,
+            Action::StatusSet,
+            Action::Rated,
+            Action::EpisodeRecorded,
+            Action::SeasonFinished,
+        ] {
+            assert_eq!(Action::from_key(&action.key()), Some(action));
+        }
+        assert_eq!(
+            renamed_title(
+                "Iron Men (2008) | aksiyon",
+                "Iron Men (2008)",
+                "Iron Man (2008)"
+            ),
+            Some("Iron Man (2008) | aksiyon".to_string())
+        );
+        assert_eq!(
+            renamed_title("Iron Men (2008) 2", "Iron Men (2008)", "X"),
+            None
+        );
+    }
+}
@@ -0,0 +1,1444 @@
+use crate::history::{watch_counts, WatchEvent};
+use crate::journal::{undoable, Action, Actor, Before, Entry};
+use crate::matching;
+use crate::namespace::Namespace;
+use crate::normalize::fold;
+use crate::pick::MAX_CHOICES;
+use crate::progress::Episode;
+use crate::rating::Rating;
+use crate::status::Status;
+use crate::storage::{CachedStorage, Kind, Storage};
+use crate::title::Title;
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// How many near misses a failed lookup offers.
+const FUZZY_LIMIT: usize = 5;
+
+struct State {
+    storage: Box<dyn Storage>,
+    films: Vec<Title>,
+    series: Vec<Title>,
+    /// Id of the next journal entry per namespace, read from storage once.
+    next_ids: HashMap<Namespace, u64>,
+}
+
+impl State {
+    fn cached(&self, kind: Kind) -> &Vec<Title> {
+        match kind {
+            Kind::Film => &self.films,
+            Kind::Series => &self.series,
+        }
+    }
+
+    fn cached_mut(&mut self, kind: Kind) -> &mut Vec<Title> {
+        match kind {
+            Kind::Film => &mut self.films,
+            Kind::Series => &mut self.series,
+        }
+    }
+
+    /// Puts a title in the watched list; whatever status or episode progress
+    /// it had is finished with. Only the first completion goes into the
+    /// history; rewatches are recorded by [`Library::mark_watched`].
+    fn complete(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<bool> {
+        let (kind, title) = (event.kind, event.title.as_str());
+        let added = self.storage.mark_watched(ns, kind, title)?;
+        if added {
+            self.storage.record_watch(ns, event)?;
+        }
+        if self.storage.statuses(ns, kind)?.contains_key(title) {
+            self.storage.set_status(ns, kind, title, None)?;
+        }
+        if kind == Kind::Series && self.storage.progress(ns)?.contains_key(title) {
+            self.storage.set_progress(ns, title, None)?;
+        }
+        Ok(added)
+    }
+
+    /// The catalogue line as stored, which may be spelled differently from
+    /// [`Title::line`] when the file was edited by hand.
+    fn stored_line(&self, kind: Kind, title: &Title) -> io::Result<Option<String>> {
+        Ok(self
+            .storage
+            .catalogue(kind)?
+            .into_iter()
+            .find(|line| Title::parse(line) == *title))
+    }
+
+    /// Everything about a title in `ns` that a journaled change may destroy.
+    fn snapshot(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<Before> {
+        Ok(Before {
+            watched: self.storage.watched(ns, kind)?.contains(title),
+            status: self.storage.statuses(ns, kind)?.remove(title),
+            episode: match kind {
+                Kind::Film => None,
+                Kind::Series => self.storage.progress(ns)?.remove(title),
+            },
+            rating: self.storage.ratings(ns, kind)?.remove(title),
+            added_at: self.storage.added_dates(kind)?.remove(title),
+            ..Before::default()
+        })
+    }
+
+    /// Puts back what [`State::snapshot`] saw, apart from the watched mark.
+    fn restore(&self, ns: &Namespace, kind: Kind, title: &str, before: &Before) -> io::Result<()> {
+        self.storage.set_status(ns, kind, title, before.status)?;
+        if kind == Kind::Series {
+            self.storage.set_progress(ns, title, before.episode)?;
+        }
+        self.storage
+            .set_rating(ns, kind, title, before.rating.clone())
+    }
+
+    fn journal(
+        &mut self,
+        actor: &Actor,
+        action: Action,
+        kind: Kind,
+        title: &str,
+        before: Before,
+        at: DateTime<Utc>,
+    ) -> io::Result<()> {
+        let entry = self.entry(actor, action, kind, title, before, at)?;
+        self.storage.append_journal(&actor.ns, &entry)
+    }
+
+    /// A journal entry with the next free id of the actor's namespace, yet
+    /// to be written.
+    fn entry(
+        &mut self,
+        actor: &Actor,
+        action: Action,
+        kind: Kind,
+        title: &str,
+        before: Before,
+        at: DateTime<Utc>,
+    ) -> io::Result<Entry> {
+        let id = match self.next_ids.get(&actor.ns) {
+            Some(id) => *id,
+            None => self
+                .storage
+                .journal(&actor.ns)?
+                .last()
+                .map_or(1, |e| e.id + 1),
+        };
+        self.next_ids.insert(actor.ns, id + 1);
+        Ok(Entry {
+            id,
+            at,
+            user: actor.user,
+            action,
+            kind,
+            title: title.to_string(),
+            before,
+            batch: None,
+        })
+    }
+
+    /// Swaps the stored catalogue line for `to`, carrying every namespace's
+    /// state over when the title itself changes. Returns `false` if the line
+    /// was gone.
+    fn replace_line(&mut self, kind: Kind, stored: &str, to: &Title) -> io::Result<bool> {
+        let from = Title::parse(stored);
+        if stored != to.line() && !self.storage.replace(kind, stored, &to.line())? {
+            return Ok(false);
+        }
+        let (old_key, new_key) = (from.to_string(), to.to_string());
+        if old_key != new_key {
+            self.storage.rename_everywhere(kind, &old_key, &new_key)?;
+        }
+        for cached in self.cached_mut(kind).iter_mut().filter(|t| **t == from) {
+            *cached = to.clone();
+        }
+        Ok(true)
+    }
+
+    /// Watched titles as [`Status::Completed`] plus every stored status.
+    fn statuses(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Status>> {
+        let mut statuses = self.storage.statuses(ns, kind)?;
+        for title in self.storage.watched(ns, kind)? {
+            statuses.insert(title, Status::Completed);
+        }
+        Ok(statuses)
+    }
+}
+
+/// Result of resolving what the user typed to a catalogue entry.
+pub enum Lookup {
+    Found(Title),
+    /// Several entries could be meant, so the user has to pick one: the
+    /// closest [`MAX_CHOICES`], and how many more matched.
+    Ambiguous(Vec<Title>, usize),
+    NotFound,
+}
+
+/// Result of [`Library::rename`].
+pub enum RenameOutcome {
+    Renamed(Title),
+    /// Another entry already has the new name.
+    Exists(Title),
+    /// The entry left the catalogue in the meantime.
+    Missing,
+}
+
+/// What finishing a season did to a series.
+pub enum SeasonOutcome {
+    /// Progress moved on to the first episode of this season.
+    NextSeason(u32),
+    /// That was the last season; the series is now in the watched list.
+    Completed,
+}
+
+/// Owns the storage backend for the whole bot. Updates are handled
+/// concurrently, so every read and read-modify-write goes through one lock
+/// and two commands can never interleave their writes.
+///
+/// The catalogue is kept in memory and only re-read from storage on
+/// [`Library::reload`]. Per-namespace lists are cached by [`CachedStorage`]
+/// once read, so most commands never wait on the disk while holding the lock.
+/// Changes still write through to it and block, so async callers should make
+/// them from a blocking thread.
+#[derive(Clone)]
+pub struct Library {
+    inner: Arc<Mutex<State>>,
+}
+
+impl Library {
+    pub fn new(storage: Box<dyn Storage>) -> io::Result<Self> {
+        let storage: Box<dyn Storage> = Box::new(CachedStorage::new(storage));
+        let films = parse_catalogue(Kind::Film, storage.catalogue(Kind::Film)?);
+        let series = parse_catalogue(Kind::Series, storage.catalogue(Kind::Series)?);
+        info!(
+            "Loaded {} films and {} series into memory.",
+            films.len(),
+            series.len()
+        );
+        Ok(Library {
+            inner: Arc::new(Mutex::new(State {
+                storage,
+                films,
+                series,
+                next_ids: HashMap::new(),
+            })),
+        })
+    }
+
+    /// A library on text files in `dir`, with per-namespace lists under
+    /// `dir/veri`. Catalogue files written beforehand are loaded.
+    #[cfg(test)]
+    pub fn in_dir(dir: &std::path::Path) -> Self {
+        let storage = crate::storage::TextStorage::new(dir, dir.join("veri"));
+        Library::new(Box::new(storage)).unwrap()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A panic while holding the lock cannot leave a half-written list
+        // behind (writes are atomic), so a poisoned lock is safe to reuse.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn catalogue(&self, kind: Kind) -> Vec<Title> {
+        self.lock().cached(kind).clone()
+    }
+
+    /// Catalogue lines that are missing their year or season count.
+    pub fn problems(&self, kind: Kind) -> Vec<(Title, &'static str)> {
+        self.lock()
+            .cached(kind)
+            .iter()
+            .filter_map(|t| t.problem(kind).map(|p| (t.clone(), p)))
+            .collect()
+    }
+
+    pub fn watched(&self, ns: &Namespace, kind: Kind) -> io::Result<HashSet<String>> {
+        self.lock().storage.watched(ns, kind)
+    }
+
+    /// Catalogue entries not yet watched in `ns` with their status, read
+    /// under a single lock.
+    pub fn unwatched(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+    ) -> io::Result<Vec<(Title, Option<Status>)>> {
+        let state = self.lock();
+        let watched = state.storage.watched(ns, kind)?;
+        let statuses = state.storage.statuses(ns, kind)?;
+        Ok(state
+            .cached(kind)
+            .iter()
+            .map(|t| (t, t.to_string()))
+            .filter(|(_, line)| !watched.contains(line))
+            .map(|(t, line)| (t.clone(), statuses.get(&line).copied()))
+            .collect())
+    }
+
+    /// When each catalogue entry was added through the bot, if known.
+    pub fn added_dates(&self, kind: Kind) -> io::Result<HashMap<String, DateTime<Utc>>> {
+        self.lock().storage.added_dates(kind)
+    }
+
+    /// Every title with a status in `ns`, completed ones included.
+    pub fn statuses(&self, ns: &Namespace, kind: Kind) -> io::Result<Vec<(Title, Status)>> {
+        let mut statuses: Vec<(Title, Status)> = self
+            .lock()
+            .statuses(ns, kind)?
+            .into_iter()
+            .map(|(title, status)| (Title::parse(&title), status))
+            .collect();
+        statuses.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+        Ok(statuses)
+    }
+
+    /// Moves a title to `status`. Completing it adds it to the watched list;
+    /// any other status takes it out again. `None` forgets the title entirely.
+    pub fn set_status(
+        &self,
+        actor: &Actor,
+        kind: Kind,
+        title: &Title,
+        status: Option<Status>,
+    ) -> io::Result<()> {
+        let mut state = self.lock();
+        let ns = &actor.ns;
+        let line = title.to_string();
+        let before = state.snapshot(ns, kind, &line)?;
+        let event = WatchEvent::now(kind, &line);
+        if status == Some(Status::Completed) {
+            state.complete(ns, &event)?;
+        } else {
+            state.storage.unmark_watched(ns, kind, &line)?;
+            state.storage.set_status(ns, kind, &line, status)?;
+            if status.is_none() && kind == Kind::Series {
+                state.storage.set_progress(ns, &line, None)?;
+            }
+        }
+        state.journal(actor, Action::StatusSet, kind, &line, before, event.at)
+    }
+
+    /// Matches the input against the full line first, then the bare name
+    /// (`13 günah` for `13 Günah (2014)`), then any line containing it, and
+    /// finally offers titles it is a likely typo of. An empty input matches
+    /// nothing.
+    pub fn lookup(&self, kind: Kind, input: &str) -> Lookup {
+        let input = fold(input);
+        if input.is_empty() {
+            return Lookup::NotFound;
+        }
+        let state = self.lock();
+
+        let mut name_matches: Vec<Title> = Vec::new();
+        let mut potential_matches: Vec<Title> = Vec::new();
+        for t in state.cached(kind) {
+            let line = fold(&t.to_string());
+            if line == input {
+                return Lookup::Found(t.clone());
+            } else if t.is_named(&input) {
+                name_matches.push(t.clone());
+            } else if line.contains(&input) {
+                potential_matches.push(t.clone());
+            }
+        }
+
+        // The same name with different years (remakes) is ambiguous.
+        if name_matches.len() == 1 {
+            return Lookup::Found(name_matches.remove(0));
+        }
+        potential_matches
+            .sort_by(|a, b| matching::score(&input, b).total_cmp(&matching::score(&input, a)));
+        name_matches.append(&mut potential_matches);
+        if !name_matches.is_empty() {
+            let more = name_matches.len().saturating_sub(MAX_CHOICES);
+            name_matches.truncate(MAX_CHOICES);
+            return Lookup::Ambiguous(name_matches, more);
+        }
+
+        // Nothing contains the input, so it may be misspelled. Even a single
+        // close match is only offered, never picked on the user's behalf.
+        let close: Vec<Title> = matching::rank(&input, state.cached(kind), FUZZY_LIMIT)
+            .into_iter()
+            .map(|m| m.title)
+            .collect();
+        if close.is_empty() {
+            Lookup::NotFound
+        } else {
+            Lookup::Ambiguous(close, 0)
+        }
+    }
+
+    /// Adds a catalogue line, tags included. Returns `false` if the title is
+    /// already in the catalogue, whatever its tags, case or diacritics.
+    pub fn add(&self, actor: &Actor, kind: Kind, line: &str) -> io::Result<bool> {
+        let mut state = self.lock();
+        let title = Title::parse(line);
+        if state.cached(kind).iter().any(|t| t.same_as(&title)) {
+            return Ok(false);
+        }
+        let added = state.storage.add(kind, &title.line())?;
+        if added {
+            let now = Utc::now();
+            state.storage.record_added(kind, &title.to_string(), now)?;
+            state.journal(
+                actor,
+                Action::Added,
+                kind,
+                &title.line(),
+                Before::default(),
+                now,
+            )?;
+            state.cached_mut(kind).push(title);
+        }
+        Ok(added)
+    }
+
+    /// Adds catalogue lines and marks titles as watched the way
+    /// [`Library::add`] and [`Library::mark_watched`] would, but writes each
+    /// list once and journals everything as one batch, undone as a whole.
+    /// Lines already in the catalogue and titles already watched are skipped;
+    /// returns which of `lines` and of `watched` were new.
+    pub fn add_batch(
+        &self,
+        actor: &Actor,
+        lines: &[(Kind, String)],
+        watched: &[(Kind, String)],
+    ) -> io::Result<(Vec<bool>, Vec<bool>)> {
+        let mut state = self.lock();
+        let ns = &actor.ns;
+        let now = Utc::now();
+        let mut entries = Vec::new();
+
+        let mut new_titles: Vec<(Kind, Title)> = Vec::new();
+        let mut added = Vec::with_capacity(lines.len());
+        for (kind, line) in lines {
+            let title = Title::parse(line);
+            let known = state
+                .cached(*kind)
+                .iter()
+                .chain(new_titles.iter().filter(|(k, _)| k == kind).map(|(_, t)| t))
+                .any(|t| t.same_as(&title));
+            if !known {
+                let before = Before::default();
+                entries.push(state.entry(
+                    actor,
+                    Action::Added,
+                    *kind,
+                    &title.line(),
+                    before,
+                    now,
+                )?);
+                new_titles.push((*kind, title));
+            }
+            added.push(!known);
+        }
+
+        let mut watched_now: HashMap<Kind, HashSet<String>> = HashMap::new();
+        for kind in Kind::ALL {
+            watched_now.insert(kind, state.storage.watched(ns, kind)?);
+        }
+        let mut new_watches: Vec<(Kind, String, Before)> = Vec::new();
+        let mut marked = Vec::with_capacity(watched.len());
+        for (kind, title) in watched {
+            let new = watched_now.entry(*kind).or_default().insert(title.clone());
+            if new {
+                let before = state.snapshot(ns, *kind, title)?;
+                new_watches.push((*kind, title.clone(), before));
+            }
+            marked.push(new);
+        }
+
+        for kind in Kind::ALL {
+            let titles: Vec<&Title> = new_titles
+                .iter()
+                .filter(|(k, _)| *k == kind)
+                .map(|(_, t)| t)
+                .collect();
+            if !titles.is_empty() {
+                let lines: Vec<String> = titles.iter().map(|t| t.line()).collect();
+                let keys: Vec<String> = titles.iter().map(|t| t.to_string()).collect();
+                state.storage.add_all(kind, &lines)?;
+                state.storage.record_added_all(kind, &keys, now)?;
+                state.cached_mut(kind).extend(titles.into_iter().cloned());
+            }
+            let keys: Vec<String> = new_watches
+                .iter()
+                .filter(|(k, _, _)| *k == kind)
+                .map(|(_, title, _)| title.clone())
+                .collect();
+            if !keys.is_empty() {
+                state.storage.mark_all_watched(ns, kind, &keys)?;
+            }
+        }
+        let mut events = Vec::new();
+        for (kind, title, before) in new_watches {
+            // What completing a title finishes with, as in `State::complete`.
+            if before.status.is_some() {
+                state.storage.set_status(ns, kind, &title, None)?;
+            }
+            if before.episode.is_some() {
+                state.storage.set_progress(ns, &title, None)?;
+            }
+            events.push(WatchEvent {
+                kind,
+                title: title.clone(),
+                at: now,
+            });
+            entries.push(state.entry(actor, Action::Watched, kind, &title, before, now)?);
+        }
+        if entries.is_empty() {
+            return Ok((added, marked));
+        }
+        state.storage.record_watches(ns, &events)?;
+
+        let batch = entries.first().map(|e| e.id);
+        for entry in &mut entries {
+            entry.batch = batch;
+        }
+        state.storage.append_journal_all(ns, &entries)?;
+        Ok((added, marked))
+    }
+
+    /// Replaces the tags of a catalogue entry and returns the updated title,
+    /// or `None` if it is no longer in the catalogue.
+    pub fn set_tags(
+        &self,
+        actor: &Actor,
+        kind: Kind,
+        title: &Title,
+        tags: Vec<String>,
+    ) -> io::Result<Option<Title>> {
+        let mut state = self.lock();
+        let Some(old) = state.stored_line(kind, title)? else {
+            return Ok(None);
+        };
+        let updated = Title {
+            tags,
+            ..title.clone()
+        };
+        if old == updated.line() {
+            return Ok(Some(updated));
+        }
+        if !state.replace_line(kind, &old, &updated)? {
+            return Ok(None);
+        }
+        let before = Before {
+            line: Some(old),
+            ..Before::default()
+        };
+        let key = updated.to_string();
+        state.journal(actor, Action::Tagged, kind, &key, before, Utc::now())?;
+        Ok(Some(updated))
+    }
+
+    /// Marks a title as watched, counting it as a rewatch if it already was.
+    /// Returns how many times it has been watched now.
+    pub fn mark_watched(&self, actor: &Actor, kind: Kind, title: &str) -> io::Result<usize> {
+        let mut state = self.lock();
+        let ns = &actor.ns;
+        let before = state.snapshot(ns, kind, title)?;
+        let event = WatchEvent::now(kind, title);
+        if !state.complete(ns, &event)? {
+            state.storage.record_watch(ns, &event)?;
+        }
+        state.journal(actor, Action::Watched, kind, title, before, event.at)?;
+        let counts = watch_counts(&state.storage.history(ns)?, kind);
+        Ok(counts.get(title).copied().unwrap_or(1))
+    }
+
+    /// Every recorded watch in `ns`, oldest first.
+    pub fn history(&self, ns: &Namespace) -> io::Result<Vec<WatchEvent>> {
+        self.lock().storage.history(ns)
+    }
+
+    /// Times each watched title of `kind` has been watched. Titles watched
+    /// before the history existed count once.
+    pub fn watch_counts(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, usize>> {
+        let state = self.lock();
+        let mut counts = watch_counts(&state.storage.history(ns)?, kind);
+        for title in state.storage.watched(ns, kind)? {
+            counts.entry(title).or_insert(1);
+        }
+        Ok(counts)
+    }
+
+    /// Rated titles in `ns`, best first.
+    pub fn ratings(&self, ns: &Namespace, kind: Kind) -> io::Result<Vec<(Title, Rating)>> {
+        let mut ratings: Vec<(Title, Rating)> = self
+            .lock()
+            .storage
+            .ratings(ns, kind)?
+            .into_iter()
+            .map(|(title, rating)| (Title::parse(&title), rating))
+            .collect();
+        ratings.sort_by(|a, b| {
+            b.1.score
+                .cmp(&a.1.score)
+                .then_with(|| a.0.name.cmp(&b.0.name))
+        });
+        Ok(ratings)
+    }
+
+    /// Rates a watched title and returns what was stored. A rating without a
+    /// note keeps the note given earlier. Returns `None` when the title is not
+    /// in the watched list.
+    pub fn rate(
+        &self,
+        actor: &Actor,
+        kind: Kind,
+        title: &Title,
+        mut rating: Rating,
+    ) -> io::Result<Option<Rating>> {
+        let mut state = self.lock();
+        let ns = &actor.ns;
+        let line = title.to_string();
+        let before = state.snapshot(ns, kind, &line)?;
+        if !before.watched {
+            return Ok(None);
+        }
+        if rating.note.is_none() {
+            rating.note = before.rating.as_ref().and_then(|old| old.note.clone());
+        }
+        state
+            .storage
+            .set_rating(ns, kind, &line, Some(rating.clone()))?;
+        state.journal(actor, Action::Rated, kind, &line, before, Utc::now())?;
+        Ok(Some(rating))
+    }
+
+    /// Series in progress with their last watched episode, by title.
+    pub fn progress(&self, ns: &Namespace) -> io::Result<Vec<(Title, Episode)>> {
+        let mut progress: Vec<(Title, Episode)> = self
+            .lock()
+            .storage
+            .progress(ns)?
+            .into_iter()
+            .map(|(title, episode)| (Title::parse(&title), episode))
+            .collect();
+        progress.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+        Ok(progress)
+    }
+
+    pub fn record_episode(&self, actor: &Actor, title: &Title, episode: Episode) -> io::Result<()> {
+        let mut state = self.lock();
+        let (ns, kind) = (&actor.ns, Kind::Series);
+        let line = title.to_string();
+        let before = state.snapshot(ns, kind, &line)?;
+        state.storage.set_progress(ns, &line, Some(episode))?;
+        state
+            .storage
+            .set_status(ns, kind, &line, Some(Status::Watching))?;
+        state.journal(
+            actor,
+            Action::EpisodeRecorded,
+            kind,
+            &line,
+            before,
+            Utc::now(),
+        )
+    }
+
+    /// Moves progress to the next season, or marks the series as watched when
+    /// `season` was its last one.
+    pub fn finish_season(
+        &self,
+        actor: &Actor,
+        title: &Title,
+        season: u32,
+    ) -> io::Result<SeasonOutcome> {
+        let mut state = self.lock();
+        let (ns, kind) = (&actor.ns, Kind::Series);
+        let line = title.to_string();
+        let before = state.snapshot(ns, kind, &line)?;
+        let event = WatchEvent::now(kind, &line);
+        let outcome = if title.seasons.is_some_and(|last| season >= last) {
+            state.complete(ns, &event)?;
+            SeasonOutcome::Completed
+        } else {
+            let next = season + 1;
+            state.storage.set_progress(
+                ns,
+                &line,
+                Some(Episode {
+                    season: next,
+                    episode: 0,
+                }),
+            )?;
+            state
+                .storage
+                .set_status(ns, kind, &line, Some(Status::Watching))?;
+            SeasonOutcome::NextSeason(next)
+        };
+        state.journal(actor, Action::SeasonFinished, kind, &line, before, event.at)?;
+        Ok(outcome)
+    }
+
+    /// Deletes a catalogue entry. What every namespace recorded about it,
+    /// watched state, status, rating, progress and history, stays where it
+    /// is, unused until the title is added back.
+    pub fn remove(&self, actor: &Actor, kind: Kind, title: &Title) -> io::Result<bool> {
+        let mut state = self.lock();
+        let Some(line) = state.stored_line(kind, title)? else {
+            return Ok(false);
+        };
+        let before = state.snapshot(&actor.ns, kind, &title.to_string())?;
+        let removed = state.storage.remove(kind, &line)?;
+        if removed {
+            state.journal(actor, Action::Removed, kind, &line, before, Utc::now())?;
+            state.cached_mut(kind).retain(|t| t != title);
+        }
+        Ok(removed)
+    }
+
+    /// Renames a catalogue entry and carries everyone's watched state,
+    /// status, rating, progress, history and journal over to the new name.
+    /// Tags are kept unless `new_line` brings its own.
+    pub fn rename(
+        &self,
+        actor: &Actor,
+        kind: Kind,
+        title: &Title,
+        new_line: &str,
+    ) -> io::Result<RenameOutcome> {
+        let mut state = self.lock();
+        let Some(old_line) = state.stored_line(kind, title)? else {
+            return Ok(RenameOutcome::Missing);
+        };
+        let mut renamed = Title::parse(new_line);
+        if renamed.tags.is_empty() {
+            renamed.tags = Title::parse(&old_line).tags;
+        }
+        if let Some(existing) = state
+            .cached(kind)
+            .iter()
+            .find(|t| *t != title && t.same_as(&renamed))
+        {
+            return Ok(RenameOutcome::Exists(existing.clone()));
+        }
+        if !state.replace_line(kind, &old_line, &renamed)? {
+            return Ok(RenameOutcome::Missing);
+        }
+        let before = Before {
+            line: Some(old_line.clone()),
+            ..Before::default()
+        };
+        let line = renamed.line();
+        state.journal(actor, Action::Renamed, kind, &line, before, Utc::now())?;
+        info!(
+            "{} '{}' renamed to '{}'.",
+            kind.title(),
+            old_line,
+            renamed.line()
+        );
+        Ok(RenameOutcome::Renamed(renamed))
+    }
+
+    /// Takes a title out of the watched list of `ns` along with its watch
+    /// history and rating. Returns `false` if it was not watched.
+    pub fn unwatch(&self, actor: &Actor, kind: Kind, title: &Title) -> io::Result<bool> {
+        let mut state = self.lock();
+        let ns = &actor.ns;
+        let line = title.to_string();
+        let mut before = state.snapshot(ns, kind, &line)?;
+        if !state.storage.unmark_watched(ns, kind, &line)? {
+            return Ok(false);
+        }
+        before.history = state
+            .storage
+            .history(ns)?
+            .into_iter()
+            .filter(|e| e.kind == kind && e.title == line)
+            .map(|e| e.at)
+            .collect();
+        state.storage.forget_history(ns, kind, &line)?;
+        state.storage.set_rating(ns, kind, &line, None)?;
+        state.journal(actor, Action::Unwatched, kind, &line, before, Utc::now())?;
+        Ok(true)
+    }
+
+    /// Every journaled change in `ns`, oldest first.
+    pub fn journal(&self, ns: &Namespace) -> io::Result<Vec<Entry>> {
+        self.lock().storage.journal(ns)
+    }
+
+    /// Reverts the actor's last `count` journaled changes in their namespace,
+    /// newest first, and returns the entries that were reverted. Undoing is
+    /// journaled too, so an entry is never reverted twice.
+    pub fn undo(&self, actor: &Actor, count: usize) -> io::Result<Vec<Entry>> {
+        let mut state = self.lock();
+        let ns = &actor.ns;
+        let entries = state.storage.journal(ns)?;
+        let mut targets: Vec<Entry> = undoable(&entries, actor.user, count)
+            .into_iter()
+            .cloned()
+            .collect();
+        // The undo of an import is listed as one change too.
+        let mut batches: HashMap<u64, u64> = HashMap::new();
+        for i in 0..targets.len() {
+            let entry = targets[i].clone();
+            let kind = entry.kind;
+            let mut title = entry.title.clone();
+            match entry.action {
+                Action::Added => {
+                    let title = Title::parse(&entry.title);
+                    if let Some(line) = state.stored_line(kind, &title)? {
+                        state.storage.remove(kind, &line)?;
+                        state.cached_mut(kind).retain(|t| *t != title);
+                    }
+                }
+                Action::Removed => {
+                    let title = Title::parse(&entry.title);
+                    let key = title.to_string();
+                    if !state.cached(kind).contains(&title)
+                        && state.storage.add(kind, &entry.title)?
+                    {
+                        state.cached_mut(kind).push(title);
+                    }
+                    if let Some(at) = entry.before.added_at {
+                        state.storage.record_added(kind, &key, at)?;
+                    }
+                    if entry.before.watched {
+                        state.storage.mark_watched(ns, kind, &key)?;
+                    }
+                    state.restore(ns, kind, &key, &entry.before)?;
+                }
+                Action::Renamed | Action::Tagged => {
+                    let current = Title::parse(&entry.title);
+                    let old = Title::parse(entry.before.line.as_deref().unwrap_or_default());
+                    let target = match entry.action {
+                        Action::Tagged => Title {
+                            tags: old.tags,
+                            ..current.clone()
+                        },
+                        _ => old,
+                    };
+                    let taken = state
+                        .cached(kind)
+                        .iter()
+                        .any(|t| *t != current && t.same_as(&target));
+                    if let (Some(line), false) = (state.stored_line(kind, &current)?, taken) {
+                        if !target.name.is_empty() && state.replace_line(kind, &line, &target)? {
+                            // Older entries follow the title back, as the
+                            // stored ones just did.
+                            let (from, to) = (current.to_string(), target.to_string());
+                            for older in targets[i + 1..].iter_mut().filter(|e| e.kind == kind) {
+                                older.retitle(&from, &to);
+                            }
+                            title = target.line();
+                        }
+                    }
+                }
+                Action::Undone(_) => continue,
+                _ => {
+                    // Watches and changes to status, rating and progress.
+                    let before = &entry.before;
+                    let watched = state.storage.watched(ns, kind)?.contains(&title);
+                    let recorded = match entry.action {
+                        Action::Watched => true,
+                        Action::StatusSet | Action::SeasonFinished => watched && !before.watched,
+                        _ => false,
+                    };
+                    if recorded {
+                        let event = WatchEvent {
+                            kind,
+                            title: title.clone(),
+                            at: entry.at,
+                        };
+                        state.storage.forget_watch(ns, &event)?;
+                    }
+                    for at in &before.history {
+                        let event = WatchEvent {
+                            kind,
+                            title: title.clone(),
+                            at: *at,
+                        };
+                        state.storage.record_watch(ns, &event)?;
+                    }
+                    if before.watched && !watched {
+                        state.storage.mark_watched(ns, kind, &title)?;
+                    } else if !before.watched && watched {
+                        state.storage.unmark_watched(ns, kind, &title)?;
+                    }
+                    state.restore(ns, kind, &title, before)?;
+                }
+            }
+            let before = Before::default();
+            let mut undo = state.entry(
+                actor,
+                Action::Undone(entry.id),
+                kind,
+                &title,
+                before,
+                Utc::now(),
+            )?;
+            undo.batch = entry.batch.map(|b| *batches.entry(b).or_insert(undo.id));
+            state.storage.append_journal(ns, &undo)?;
+            info!(
+                "{} undid journal entry #{} ({}).",
+                ns,
+                entry.id,
+                entry.describe()
+            );
+        }
+        Ok(targets)
+    }
+
+    /// Files that may be edited by hand and should trigger [`Library::reload`].
+    pub fn source_files(&self) -> Vec<(Kind, PathBuf)> {
+        self.lock().storage.source_files()
+    }
+
+    /// Re-reads one catalogue from storage and logs what changed.
+    pub fn reload(&self, kind: Kind) -> io::Result<()> {
+        let mut state = self.lock();
+        let fresh = parse_catalogue(kind, state.storage.catalogue(kind)?);
+
+        let old: HashSet<String> = state.cached(kind).iter().map(Title::line).collect();
+        let new: HashSet<String> = fresh.iter().map(Title::line).collect();
+        let mut added: Vec<&str> = new.difference(&old).map(String::as_str).collect();
+        let mut removed: Vec<&str> = old.difference(&new).map(String::as_str).collect();
+        added.sort();
+        removed.sort();
+
+        if added.is_empty() && removed.is_empty() {
+            debug!("{} reloaded, nothing changed.", kind.catalogue_file());
+        } else {
+            info!(
+                "{} reloaded: {} added {:?}, {} removed {:?}, {} total.",
+                kind.catalogue_file(),
+                added.len(),
+                added,
+                removed.len(),
+                removed,
+                fresh.len()
+            );
+        }
+
+        *state.cached_mut(kind) = fresh;
+        Ok(())
+    }
+}
+
+fn parse_catalogue(kind: Kind, lines: Vec<String>) -> Vec<Title> {
+    let titles: Vec<Title> = lines.iter().map(|l| Title::parse(l)).collect();
+    for title in &titles {
+        if let Some(problem) = title.problem(kind) {
+            warn!(
+                "{}: '{}' satırı hatalı ({}).",
+                kind.catalogue_file(),
+                title,
+                problem
+            );
+        }
+    }
+    titles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::TextStorage;
+    use std::thread;
+
+    fn by(ns: &Namespace) -> Actor {
+        Actor {
+            ns: *ns,
+            user: None,
+        }
+    }
+
+    #[test]
+    fn concurrent_marks_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = Library::in_dir(dir.path());
+        let ns = Namespace::User(42);
+
+        thread::scope(|scope| {
+            for i in 0..64 {
+                let library = library.clone();
+                scope.spawn(move || {
+                    library
+                        .mark_watched(&by(&ns), Kind::Film, &format!("Film {} (2000)", i))
+                        .unwrap();
+                });
+            }
+        });
+
+        let watched = library.watched(&ns, Kind::Film).unwrap();
+        assert_eq!(watched.len(), 64);
+        for i in 0..64 {
+            assert!(watched.contains(&format!("Film {} (2000)", i)));
+        }
+    }
+
+    #[test]
+    fn each_namespace_keeps_its_own_lists() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("diziler.txt"), "Dark (3 Sezon)\n").unwrap();
+        let library = Library::in_dir(dir.path());
+        let (alice, group) = (Namespace::User(1), Namespace::Chat(-1));
+        let dark = Title::parse("Dark (3 Sezon)");
+
+        library
+            .mark_watched(&by(&alice), Kind::Series, "Dark (3 Sezon)")
+            .unwrap();
+        library
+            .rate(
+                &by(&alice),
+                Kind::Series,
+                &dark,
+                Rating::new(9, None).unwrap(),
+            )
+            .unwrap();
+        library
+            .record_episode(
+                &by(&group),
+                &dark,
+                Episode {
+                    season: 1,
+                    episode: 3,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(library.watched(&alice, Kind::Series).unwrap().len(), 1);
+        assert!(library.progress(&alice).unwrap().is_empty());
+        assert!(library.watched(&group, Kind::Series).unwrap().is_empty());
+        assert!(library.ratings(&group, Kind::Series).unwrap().is_empty());
+        assert!(library.history(&group).unwrap().is_empty());
+        assert_eq!(library.progress(&group).unwrap().len(), 1);
+        assert!(dir
+            .path()
+            .join("veri/kullanici_1/izlenen_diziler.txt")
+            .is_file());
+        assert!(!dir.path().join("izlenen_diziler.txt").exists());
+    }
+
+    #[test]
+    fn completing_a_title_moves_it_to_the_watched_list() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("filmler.txt"), "Matilda (1996)\n").unwrap();
+        let library = Library::in_dir(dir.path());
+        let ns = Namespace::User(7);
+        let matilda = Title::parse("Matilda (1996)");
+
+        library
+            .set_status(&by(&ns), Kind::Film, &matilda, Some(Status::Watching))
+            .unwrap();
+        assert_eq!(
+            library.unwatched(&ns, Kind::Film).unwrap(),
+            vec![(matilda.clone(), Some(Status::Watching))]
+        );
+
+        library
+            .set_status(&by(&ns), Kind::Film, &matilda, Some(Status::Completed))
+            .unwrap();
+        assert!(library.unwatched(&ns, Kind::Film).unwrap().is_empty());
+        assert_eq!(
+            library.statuses(&ns, Kind::Film).unwrap(),
+            vec![(matilda.clone(), Status::Completed)]
+        );
+
+        library
+            .set_status(&by(&ns), Kind::Film, &matilda, Some(Status::Dropped))
+            .unwrap();
+        assert!(library.watched(&ns, Kind::Film).unwrap().is_empty());
+        assert_eq!(
+            library.unwatched(&ns, Kind::Film).unwrap(),
+            vec![(matilda, Some(Status::Dropped))]
+        );
+    }
+
+    #[test]
+    fn ratings_need_a_watched_title_and_keep_their_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = Library::in_dir(dir.path());
+        let ns = Namespace::User(7);
+        let matilda = Title::parse("Matilda (1996)");
+        let rating =
+            |score, note: Option<&str>| Rating::new(score, note.map(str::to_string)).unwrap();
+
+        assert_eq!(
+            library
+                .rate(&by(&ns), Kind::Film, &matilda, rating(8, None))
+                .unwrap(),
+            None
+        );
+        library
+            .mark_watched(&by(&ns), Kind::Film, &matilda.to_string())
+            .unwrap();
+        library
+            .rate(
+                &by(&ns),
+                Kind::Film,
+                &matilda,
+                rating(8, Some("Çok  güzeldi")),
+            )
+            .unwrap();
+        assert_eq!(
+            library
+                .rate(&by(&ns), Kind::Film, &matilda, rating(9, None))
+                .unwrap(),
+            Some(rating(9, Some("Çok güzeldi")))
+        );
+        assert_eq!(
+            library.ratings(&ns, Kind::Film).unwrap(),
+            vec![(matilda, rating(9, Some("Çok güzeldi")))]
+        );
+    }
+
+    #[test]
+    fn rewatches_are_counted_in_the_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = Library::in_dir(dir.path());
+        let ns = Namespace::User(7);
+        let matilda = Title::parse("Matilda (1996)");
+
+        assert_eq!(
+            library
+                .mark_watched(&by(&ns), Kind::Film, "Matilda (1996)")
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            library
+                .mark_watched(&by(&ns), Kind::Film, "Matilda (1996)")
+                .unwrap(),
+            2
+        );
+        library
+            .set_status(&by(&ns), Kind::Film, &matilda, Some(Status::Completed))
+            .unwrap();
+
+        assert_eq!(library.watched(&ns, Kind::Film).unwrap().len(), 1);
+        let history = library.history(&ns).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].at <= history[1].at);
+        assert_eq!(
+            library.watch_counts(&ns, Kind::Film).unwrap()["Matilda (1996)"],
+            2
+        );
+    }
+
+    #[test]
+    fn tags_are_written_back_to_the_catalogue() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("filmler.txt"), "Matilda (1996)|aile\n").unwrap();
+        let library = Library::in_dir(dir.path());
+        let matilda = Title::parse("Matilda (1996)");
+
+        assert!(!library
+            .add(
+                &by(&Namespace::User(7)),
+                Kind::Film,
+                "Matilda (1996) | komedi"
+            )
+            .unwrap());
+        let updated = library
+            .set_tags(
+                &by(&Namespace::User(7)),
+                Kind::Film,
+                &matilda,
+                vec!["komedi".into(), "aile".into()],
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.tags, vec!["komedi", "aile"]);
+        assert_eq!(library.catalogue(Kind::Film)[0].tags, updated.tags);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("filmler.txt")).unwrap(),
+            "Matilda (1996) | komedi, aile\n"
+        );
+    }
+
+    #[test]
+    fn lookups_and_duplicates_ignore_turkish_case_and_diacritics() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("diziler.txt"),
+            "Zamanın Kapıları (2 Sezon)\n",
+        )
+        .unwrap();
+        let library = Library::in_dir(dir.path());
+
+        for input in ["ZAMANIN KAPILARI", "zamanin kapilari (2 sezon)"] {
+            assert!(matches!(
+                library.lookup(Kind::Series, input),
+                Lookup::Found(t) if t.name == "Zamanın Kapıları"
+            ));
+        }
+        assert!(!library
+            .add(
+                &by(&Namespace::User(7)),
+                Kind::Series,
+                "Zamanin Kapilari (2 Sezon)"
+            )
+            .unwrap());
+        assert_eq!(library.catalogue(Kind::Series).len(), 1);
+    }
+
+    #[test]
+    fn loose_lookups_offer_a_few_titles_and_count_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines: String = (1..=20)
+            .map(|i| format!("Film {} ({})\n", i, 1990 + i))
+            .collect();
+        std::fs::write(dir.path().join("filmler.txt"), lines).unwrap();
+        let library = Library::in_dir(dir.path());
+
+        let Lookup::Ambiguous(candidates, more) = library.lookup(Kind::Film, "fil") else {
+            panic!("expected several matches");
+        };
+        assert_eq!((candidates.len(), more), (MAX_CHOICES, 20 - MAX_CHOICES));
+        for blank in ["", "   "] {
+            assert!(matches!(
+                library.lookup(Kind::Film, blank),
+                Lookup::NotFound
+            ));
+        }
+    }
+
+    #[test]
+    fn renaming_carries_watched_state_and_removing_keeps_it() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("filmler.txt"),
+            "Iron Men (2008) | aksiyon\n",
+        )
+        .unwrap();
+        let library = Library::in_dir(dir.path());
+        let (alice, bob) = (Namespace::User(1), Namespace::User(2));
+        let typo = Title::parse("Iron Men (2008)");
+        for ns in [&alice, &bob] {
+            library
+                .mark_watched(&by(ns), Kind::Film, "Iron Men (2008)")
+                .unwrap();
+        }
+        library
+            .rate(
+                &by(&alice),
+                Kind::Film,
+                &typo,
+                Rating::new(9, None).unwrap(),
+            )
+            .unwrap();
+
+        let RenameOutcome::Renamed(fixed) = library
+            .rename(&by(&alice), Kind::Film, &typo, "Iron Man (2008)")
+            .unwrap()
+        else {
+            panic!("rename failed");
+        };
+        assert_eq!(fixed.line(), "Iron Man (2008) | aksiyon");
+        for ns in [&alice, &bob] {
+            let watched = library.watched(ns, Kind::Film).unwrap();
+            assert!(watched.contains("Iron Man (2008)") && watched.len() == 1);
+            assert_eq!(library.history(ns).unwrap()[0].title, "Iron Man (2008)");
+        }
+        assert_eq!(library.ratings(&alice, Kind::Film).unwrap()[0].0, fixed);
+
+        assert!(library.unwatch(&by(&bob), Kind::Film, &fixed).unwrap());
+        assert!(library.watched(&bob, Kind::Film).unwrap().is_empty());
+        assert!(library.history(&bob).unwrap().is_empty());
+
+        assert!(library.remove(&by(&alice), Kind::Film, &fixed).unwrap());
+        assert!(library.catalogue(Kind::Film).is_empty());
+        assert!(library
+            .watched(&alice, Kind::Film)
+            .unwrap()
+            .contains("Iron Man (2008)"));
+        assert_eq!(library.ratings(&alice, Kind::Film).unwrap().len(), 1);
+        assert_eq!(library.history(&alice).unwrap().len(), 1);
+    }
+
+    fn both_backends(dir: &std::path::Path) -> [Box<dyn Storage>; 2] {
+        [
+            Box::new(TextStorage::new(dir, dir.join("veri"))),
+            Box::new(crate::storage::SqliteStorage::open(dir.join("kutuphane.db")).unwrap()),
+        ]
+    }
+
+    #[test]
+    fn undo_walks_back_through_the_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        for storage in both_backends(dir.path()) {
+            let library = Library::new(storage).unwrap();
+            let ns = Namespace::Chat(-5);
+            let (alice, bob) = (Actor { ns, user: Some(1) }, Actor { ns, user: Some(2) });
+            let matilda = Title::parse("Matilda (1996)");
+            let film = Kind::Film;
+
+            library
+                .add(&alice, film, "Matilda (1996) | komedi")
+                .unwrap();
+            library
+                .set_status(&alice, film, &matilda, Some(Status::Planned))
+                .unwrap();
+            library
+                .mark_watched(&alice, film, "Matilda (1996)")
+                .unwrap();
+            library
+                .mark_watched(&alice, film, "Matilda (1996)")
+                .unwrap();
+            library
+                .rate(&alice, film, &matilda, Rating::new(8, None).unwrap())
+                .unwrap();
+            assert!(library.remove(&alice, film, &matilda).unwrap());
+            assert!(library.undo(&bob, 1).unwrap().is_empty());
+
+            let undone = library.undo(&alice, 1).unwrap();
+            assert_eq!(undone[0].action, Action::Removed);
+            assert_eq!(library.catalogue(film)[0].tags, vec!["komedi"]);
+            assert!(library
+                .watched(&ns, film)
+                .unwrap()
+                .contains("Matilda (1996)"));
+            assert_eq!(library.ratings(&ns, film).unwrap()[0].1.score, 8);
+
+            assert_eq!(library.undo(&alice, 1).unwrap()[0].action, Action::Rated);
+            assert!(library.ratings(&ns, film).unwrap().is_empty());
+            assert_eq!(library.history(&ns).unwrap().len(), 2);
+
+            library.undo(&alice, 1).unwrap();
+            assert_eq!(library.history(&ns).unwrap().len(), 1);
+
+            library.undo(&alice, 1).unwrap();
+            assert!(library.watched(&ns, film).unwrap().is_empty());
+            assert!(library.history(&ns).unwrap().is_empty());
+            assert_eq!(
+                library.unwatched(&ns, film).unwrap(),
+                vec![(matilda.clone(), Some(Status::Planned))]
+            );
+
+            assert_eq!(library.undo(&alice, 5).unwrap().len(), 2);
+            assert!(library.catalogue(film).is_empty());
+            assert!(library.undo(&alice, 1).unwrap().is_empty());
+            assert_eq!(library.journal(&ns).unwrap().len(), 12);
+        }
+    }
+
+    #[test]
+    fn undo_brings_back_unwatched_titles_renames_and_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        for storage in both_backends(dir.path()) {
+            let library = Library::new(storage).unwrap();
+            let ns = Namespace::User(3);
+            let actor = by(&ns);
+            let (film, series) = (Kind::Film, Kind::Series);
+            let typo = Title::parse("Iron Men (2008)");
+            let dark = Title::parse("Dark (3 Sezon)");
+
+            library.add(&actor, series, "Dark (3 Sezon)").unwrap();
+            let episode = Episode {
+                season: 1,
+                episode: 3,
+            };
+            library.record_episode(&actor, &dark, episode).unwrap();
+            library.finish_season(&actor, &dark, 1).unwrap();
+            library.undo(&actor, 1).unwrap();
+            assert_eq!(
+                library.progress(&ns).unwrap(),
+                vec![(dark.clone(), episode)]
+            );
+            library.undo(&actor, 1).unwrap();
+            assert!(library.progress(&ns).unwrap().is_empty());
+            assert!(library.statuses(&ns, series).unwrap().is_empty());
+
+            library
+                .add(&actor, film, "Iron Men (2008) | aksiyon")
+                .unwrap();
+            library
+                .mark_watched(&actor, film, "Iron Men (2008)")
+                .unwrap();
+            library
+                .mark_watched(&actor, film, "Iron Men (2008)")
+                .unwrap();
+            library
+                .rate(&actor, film, &typo, Rating::new(9, None).unwrap())
+                .unwrap();
+            let RenameOutcome::Renamed(fixed) = library
+                .rename(&actor, film, &typo, "Iron Man (2008)")
+                .unwrap()
+            else {
+                panic!("rename failed");
+            };
+            let journal = library.journal(&ns).unwrap();
+            assert!(journal
+                .iter()
+                .filter(|e| e.kind == film)
+                .all(|e| e.title.starts_with("Iron Man (2008)")));
+            library
+                .set_tags(&actor, film, &fixed, vec!["bilimkurgu".into()])
+                .unwrap();
+            assert!(library.unwatch(&actor, film, &fixed).unwrap());
+            assert!(library.history(&ns).unwrap().is_empty());
+
+            library.undo(&actor, 1).unwrap();
+            assert_eq!(
+                library.watch_counts(&ns, film).unwrap()["Iron Man (2008)"],
+                2
+            );
+            assert_eq!(library.ratings(&ns, film).unwrap()[0].1.score, 9);
+
+            library.undo(&actor, 1).unwrap();
+            assert_eq!(
+                library.catalogue(film)[0].line(),
+                "Iron Man (2008) | aksiyon"
+            );
+
+            // The rename and everything before it, in one go.
+            assert_eq!(library.undo(&actor, 4).unwrap().len(), 4);
+            assert_eq!(
+                library.catalogue(film)[0].line(),
+                "Iron Men (2008) | aksiyon"
+            );
+            assert!(library.watched(&ns, film).unwrap().is_empty());
+            assert!(library.history(&ns).unwrap().is_empty());
+            assert!(library.ratings(&ns, film).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn removing_a_title_keeps_what_other_namespaces_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        for storage in both_backends(dir.path()) {
+            let library = Library::new(storage).unwrap();
+            let (group, alice) = (Namespace::Chat(-8), Namespace::User(4));
+            let (in_group, by_alice) = (by(&group), by(&alice));
+            let film = Kind::Film;
+            let matilda = Title::parse("Matilda (1996)");
+
+            library.add(&in_group, film, "Matilda (1996)").unwrap();
+            library
+                .mark_watched(&by_alice, film, "Matilda (1996)")
+                .unwrap();
+            library
+                .rate(&by_alice, film, &matilda, Rating::new(8, None).unwrap())
+                .unwrap();
+            assert!(library.remove(&in_group, film, &matilda).unwrap());
+            assert!(library
+                .watched(&alice, film)
+                .unwrap()
+                .contains("Matilda (1996)"));
+
+            library.undo(&in_group, 1).unwrap();
+            assert_eq!(library.catalogue(film), vec![matilda.clone()]);
+            assert_eq!(library.ratings(&alice, film).unwrap()[0].1.score, 8);
+
+            // Undoing the add leaves what was recorded since alone too.
+            library.undo(&in_group, 1).unwrap();
+            assert!(library.catalogue(film).is_empty());
+            assert!(library
+                .watched(&alice, film)
+                .unwrap()
+                .contains("Matilda (1996)"));
+            assert_eq!(
+                library.watch_counts(&alice, film).unwrap()["Matilda (1996)"],
+                1
+            );
+        }
+    }
+}
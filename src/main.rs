@@ -1,12 +1,68 @@
-use dotenv;
-use log::info;
-use rand::seq::SliceRandom;
-use std::collections::HashSet;
-use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
-use teloxide::{prelude::*, utils::command::BotCommands};
+mod buttons;
+mod charts;
+mod export;
+mod filter;
+mod history;
+mod import;
+mod journal;
+mod library;
+mod matching;
+mod namespace;
+mod normalize;
+mod paging;
+mod pick;
+mod progress;
+mod rating;
+mod recommend;
+mod review;
+mod search;
+mod stats;
+mod status;
+mod storage;
+mod title;
+mod watcher;
+
+use buttons::{suggestion_keyboard, title_id, Action, ButtonData};
+use chrono::{Datelike, Local, Utc};
+use filter::{TitleFilter, Wanted};
+use journal::Actor;
+use library::{Library, Lookup, RenameOutcome, SeasonOutcome};
+use log::{error, info, warn};
+use namespace::Namespace;
+use paging::{page_count, page_keyboard, page_of, PageData, View};
+use pick::{choice_keyboard, Choice, Pending, PickDialogue, PickState};
+use progress::{split_episode_arg, split_season_arg, Episode};
+use rating::{split_rating_arg, Rating};
+use recommend::{Candidate, Recommender, Taste};
+use review::YearReview;
+use search::SearchQuery;
+use stats::Stats;
+use status::{split_status_arg, Status};
+use std::collections::HashMap;
+use std::io;
+use storage::Kind;
+use teloxide::dispatching::dialogue::InMemStorage;
+use teloxide::net::Download;
+use teloxide::types::{Document, InlineKeyboardMarkup, InputFile};
+use teloxide::{prelude::*, utils::command::BotCommands, ApiError, RequestError};
+use title::{parse_tags, ListOrder, Title};
 
 const MAX_MESSAGE_LENGTH: usize = 4000;
+const HISTORY_PAGE_SIZE: usize = 15;
+const MAX_SUGGESTIONS: usize = 10;
+const MAX_UNDO: usize = 20;
+/// How many near misses a search without results offers.
+const SEARCH_SUGGESTIONS: usize = 5;
+/// Invalid lines an import summary lists; the rest are only counted.
+const MAX_INVALID_SHOWN: usize = 10;
+const IMPORT_HELP: &str = "Bana bir dosya gönder, içindeki başlıkları eklerim:\n\
+    • .txt: her satıra bir başlık, örn: `Matilda (1996) | komedi`. Gönderirken açıklamaya `film` veya `dizi` yaz.\n\
+    • .csv: `ad` sütunu şart; `tur`, `etiketler`, `katalogda` ve `izlendi` sütunları da okunur.\n\
+    • .json: başlık listesi ya da `/disa_aktar json` ile aldığın dosya.\n\
+    Açıklamaya `izlenen` de yazarsan katalog yerine izlenenler listene eklerim. Zaten olanlar atlanır. \
+    Gruplarda açıklamanın `/ice_aktar` ile başlaması gerekir, örn: `/ice_aktar film`. \
+    Bir içe aktarma tek bir değişiklik sayılır, `/geri_al` ile hepsi birden geri alınır. \
+    Dışa aktarılmış dosyalardan yalnızca başlıkları, etiketleri ve izlenip izlenmediğini alırım; puan, not, tarih, durum ve bölüm bilgileri aktarılmaz.";
 
 #[tokio::main]
 async fn main() {
@@ -14,8 +70,59 @@ async fn main() {
     pretty_env_logger::init();
     info!("Starting command bot...");
 
+    let storage = storage::from_env().expect("❌ Depolama başlatılamadı!");
+    let library = Library::new(storage).expect("❌ Katalog yüklenemedi!");
+    let _watcher = watcher::spawn(library.clone()).unwrap_or_else(|e| {
+        warn!("Catalogue hot reload is disabled: {}", e);
+        None
+    });
     let bot = Bot::from_env();
-    Command::repl(bot, answer).await;
+    let handler = dptree::entry()
+        .branch(
+            Update::filter_message()
+                .filter_command::<Command>()
+                .enter_dialogue::<Message, InMemStorage<PickState>, PickState>()
+                .endpoint(answer),
+        )
+        .branch(
+            Update::filter_message()
+                .filter_map(|msg: Message| {
+                    let document = msg.document()?;
+                    let name = document.file_name.as_deref().unwrap_or("");
+                    let caption = msg.caption().unwrap_or("");
+                    import::is_requested(name, caption, msg.chat.is_private())
+                        .then(|| document.clone())
+                })
+                .endpoint(import_document),
+        )
+        .branch(
+            Update::filter_callback_query()
+                .branch(
+                    dptree::filter_map(|query: CallbackQuery| {
+                        query.data.as_deref().and_then(Choice::parse)
+                    })
+                    .enter_dialogue::<CallbackQuery, InMemStorage<PickState>, PickState>()
+                    .endpoint(answer_choice),
+                )
+                .branch(
+                    dptree::filter_map(|query: CallbackQuery| {
+                        query.data.as_deref().and_then(PageData::parse)
+                    })
+                    .endpoint(answer_page),
+                )
+                .endpoint(answer_button),
+        );
+
+    Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![
+            library,
+            Recommender::from_env(),
+            InMemStorage::<PickState>::new()
+        ])
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
 }
 
 #[derive(BotCommands, Clone)]
@@ -27,353 +134,332 @@ enum Command {
     #[command(description = "Tüm komutların listesini ve açıklamalarını gösterir.")]
     Yardim,
 
-    #[command(description = "İzlenmemiş filmler listesinden rastgele bir film önerir.")]
-    FilmOner,
+    #[command(
+        description = "İzlenmemiş filmler listesinden rastgele film önerir, bırakılanları atlar. Önerinin altındaki düğmelerle izledim, sonra veya başka öner diyebilirsin. `devam` yarım kalanları öne çıkarır, etiket ve yıl aralığı daraltır. Kullanım: /film_oner [adet] [devam] [etiket...] [2010-2020]"
+    )]
+    FilmOner(String),
 
-    #[command(description = "İzlenmemiş diziler listesinden rastgele bir dizi önerir.")]
-    DiziOner,
+    #[command(
+        description = "İzlenmemiş diziler listesinden rastgele dizi önerir, bırakılanları atlar. Önerinin altındaki düğmelerle izledim, sonra veya başka öner diyebilirsin. `devam` yarım kalanları öne çıkarır, etiket daraltır. Kullanım: /dizi_oner [adet] [devam] [etiket...]"
+    )]
+    DiziOner(String),
 
     #[command(
-        description = "Önerilen veya izlediğin bir filmi 'izlenenler' listene ekler. Kullanım: /izlenen_film_ekle <Film Adı>"
+        description = "Önerilen veya izlediğin bir filmi 'izlenenler' listene ekler. İstersen puan ve not da verebilirsin. Kullanım: /izlenen_film_ekle <Film Adı> [| puan | not]"
     )]
     IzlenenFilmEkle(String),
 
     #[command(
-        description = "Önerilen veya izlediğin bir diziyi 'izlenenler' listene ekler. Kullanım: /izlenen_dizi_ekle <Dizi Adı>"
+        description = "Önerilen veya izlediğin bir diziyi 'izlenenler' listene ekler. İstersen puan ve not da verebilirsin. Kullanım: /izlenen_dizi_ekle <Dizi Adı> [| puan | not]"
     )]
     IzlenenDiziEkle(String),
 
     #[command(
-        description = "Yeni bir filmi ana filmler listesine ekler. Kullanım: /film_ekle <Film Adı>"
+        description = "Yeni bir filmi ana filmler listesine ekler. Kullanım: /film_ekle <Film Adı> [| etiket1, etiket2]"
     )]
     FilmEkle(String),
 
     #[command(
-        description = "Yeni bir diziyi ana diziler listesine ekler. Kullanım: /dizi_ekle <Dizi Adı>"
+        description = "Yeni bir diziyi ana diziler listesine ekler. Kullanım: /dizi_ekle <Dizi Adı> [| etiket1, etiket2]"
     )]
     DiziEkle(String),
 
-    #[command(description = "İzlediğin tüm filmleri listeler.")]
-    IzlenenFilmler,
+    #[command(
+        description = "Bir filmin etiketlerini değiştirir, boş bırakırsan siler. Kullanım: /film_etiket <Film Adı> | korku, gerilim"
+    )]
+    FilmEtiket(String),
+
+    #[command(
+        description = "Bir dizinin etiketlerini değiştirir, boş bırakırsan siler. Kullanım: /dizi_etiket <Dizi Adı> | dram, suç"
+    )]
+    DiziEtiket(String),
+
+    #[command(
+        description = "Bir filmi ana filmler listesinden siler; izlenme bilgileri saklanır, yeniden eklenirse geri gelir. Kullanım: /film_sil <Film Adı>"
+    )]
+    FilmSil(String),
+
+    #[command(
+        description = "Bir diziyi ana diziler listesinden siler; izlenme bilgileri saklanır, yeniden eklenirse geri gelir. Kullanım: /dizi_sil <Dizi Adı>"
+    )]
+    DiziSil(String),
+
+    #[command(
+        description = "Bir film veya dizinin adını değiştirir, izlenme bilgileri yeni ada taşınır. Kullanım: /yeniden_adlandir [film|dizi] <Eski Ad> | <Yeni Ad>"
+    )]
+    YenidenAdlandir(String),
+
+    #[command(
+        description = "Yanlışlıkla izlenenlere eklediğin bir film veya diziyi geri çıkarır. Kullanım: /izlenmedi [film|dizi] <Ad>"
+    )]
+    Izlenmedi(String),
+
+    #[command(
+        description = "Son yaptığın değişiklikleri (ekleme, izleme, silme, puan, durum, bölüm, etiket, ad) geri alır. Kullanım: /geri_al [adet]"
+    )]
+    GeriAl(String),
+
+    #[command(description = "Bu listede kimin neyi değiştirdiğini, en yeniden eskiye gösterir.")]
+    Gunluk,
+
+    #[command(
+        description = "İzlediğin tüm filmleri listeler. Kullanım: /izlenen_filmler [ad|yil]"
+    )]
+    IzlenenFilmler(String),
+
+    #[command(
+        description = "İzlediğin tüm dizileri listeler. Kullanım: /izlenen_diziler [ad|sezon]"
+    )]
+    IzlenenDiziler(String),
+
+    #[command(
+        description = "Ana filmler listesindeki tüm filmleri gösterir. Kullanım: /tum_filmler [ad|yil]"
+    )]
+    TumFilmler(String),
+
+    #[command(
+        description = "Ana diziler listesindeki tüm dizileri gösterir. Kullanım: /tum_diziler [ad|sezon]"
+    )]
+    TumDiziler(String),
+
+    #[command(
+        description = "Henüz izlemediğin filmleri listeler. Kullanım: /izlenmemis_filmler [ad|yil]"
+    )]
+    IzlenmemisFilmler(String),
+
+    #[command(
+        description = "Henüz izlemediğin dizileri listeler. Kullanım: /izlenmemis_diziler [ad|sezon]"
+    )]
+    IzlenmemisDiziler(String),
+
+    #[command(
+        description = "Filmlerde ve dizilerde arar, kelimenin bir kısmını yazman yeterli. Filtreler: tur:film|dizi, yil:2019 veya yil:2010-2020, izlendi:evet|hayir. Kullanım: /ara <sorgu> [filtreler]"
+    )]
+    Ara(String),
+
+    #[command(
+        description = "Bir dizide izlediğin son bölümü kaydeder. Kullanım: /bolum_izledim <Dizi Adı> S02E05"
+    )]
+    BolumIzledim(String),
+
+    #[command(
+        description = "Bir dizinin sezonunu bitirdiğini kaydeder, son sezonsa diziyi izlenenlere ekler. Kullanım: /sezon_bitti <Dizi Adı> [sezon]"
+    )]
+    SezonBitti(String),
+
+    #[command(description = "Devam ettiğin dizilerde sıradaki bölümü gösterir.")]
+    Devam,
+
+    #[command(
+        description = "Bir filmin durumunu değiştirir: planli, izliyorum, beklemede, birakildi, tamamlandi veya yok. Kullanım: /film_durum <Film Adı> <durum>"
+    )]
+    FilmDurum(String),
+
+    #[command(
+        description = "Bir dizinin durumunu değiştirir: planli, izliyorum, beklemede, birakildi, tamamlandi veya yok. Kullanım: /dizi_durum <Dizi Adı> <durum>"
+    )]
+    DiziDurum(String),
+
+    #[command(description = "Durum verdiğin film ve dizileri durumlarına göre listeler.")]
+    Durumlar,
 
-    #[command(description = "İzlediğin tüm dizileri listeler.")]
-    IzlenenDiziler,
+    #[command(
+        description = "İzlediğin film ve dizileri tarihleriyle, en yeniden eskiye listeler. Kullanım: /gecmis [sayfa]"
+    )]
+    Gecmis(String),
+
+    #[command(
+        description = "İzlediğin bir filme veya diziye 1-10 arası puan ve istersen not verir. Kullanım: /puanla [film|dizi] <Ad> | <puan> | [not]"
+    )]
+    Puanla(String),
+
+    #[command(
+        description = "Puan verdiğin film ve dizileri en yüksek puandan başlayarak listeler."
+    )]
+    EnIyiler,
 
-    #[command(description = "Ana filmler listesindeki tüm filmleri gösterir.")]
-    TumFilmler,
+    #[command(
+        description = "Kaç film ve dizi izlediğini, yıllara ve aylara göre dağılımı ve kalan sezonları gösterir."
+    )]
+    Istatistik,
+
+    #[command(
+        description = "İstatistiklerini grafik olarak gönderir: aylara göre izlemeler, yıllara göre filmler ve tamamlanma oranı."
+    )]
+    Grafikler,
+
+    #[command(
+        description = "Bir yılı özetler: bitirdiğin film ve diziler, en yüksek puanlıların, en uzun izleme serin ve en çok izlediğin tür. Sonuna `kart` eklersen görsel bir kart da gönderir. Kullanım: /yil_ozeti [yıl] [kart]"
+    )]
+    YilOzeti(String),
 
-    #[command(description = "Ana diziler listesindeki tüm dizileri gösterir.")]
-    TumDiziler,
+    #[command(
+        description = "Kataloğunu, izlediklerini, puanlarını ve tarihleri bir dosya olarak gönderir. Markdown okumak içindir. Kullanım: /disa_aktar csv|json|md"
+    )]
+    DisaAktar(String),
 
-    #[command(description = "Henüz izlemediğin filmleri listeler.")]
-    IzlenmemisFilmler,
+    #[command(
+        description = "Bir .txt, .csv veya .json dosyasındaki başlıkları topluca nasıl ekleyeceğini anlatır."
+    )]
+    IceAktar,
 
-    #[command(description = "Henüz izlemediğin dizileri listeler.")]
-    IzlenmemisDiziler,
+    #[command(description = "Yıl veya sezon bilgisi eksik olan katalog satırlarını gösterir.")]
+    HataliKayitlar,
 
     #[command(description = "Bota merhaba der ve sana özel bir mesaj gönderir.")]
     Merhaba,
 }
 
-async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
+async fn answer(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    library: Library,
+    recommender: Recommender,
+    dialogue: PickDialogue,
+) -> ResponseResult<()> {
+    let actor = Actor::from_message(&msg);
+    let ns = actor.ns;
     match cmd {
         Command::Yardim => {
             bot.send_message(msg.chat.id, Command::descriptions().to_string())
                 .await?;
         }
 
-        Command::FilmOner => match get_random_unwatched_film() {
-            Some(film) => {
-                bot.send_message(
-                    msg.chat.id,
-                    format!("🎬 Film Önerisi: {}. İzlediğinizde `/izlenen_film_ekle {}` komutunu kullanın.", film, film)
-                ).await?;
-            }
-            None => {
-                bot.send_message(
-                    msg.chat.id,
-                    "✅ Tüm filmler önerildi! `izlenen_filmler.txt` dosyasını silerek listeyi sıfırlayabilirsin.",
-                )
-                .await?;
-            }
-        },
-
-        Command::DiziOner => match get_random_unwatched_series() {
-            Some(series) => {
-                bot.send_message(
-                    msg.chat.id,
-                    format!("📺 Dizi Önerisi: {}. İzlediğinizde `/izlenen_dizi_ekle {}` komutunu kullanın.", series, series)
-                ).await?;
-            }
-            None => {
-                bot.send_message(
-                    msg.chat.id,
-                    "✅ Tüm diziler önerildi! `izlenen_diziler.txt` dosyasını silerek listeyi sıfırlayabilirsin.",
-                )
-                .await?;
-            }
-        },
-
-        Command::IzlenenFilmEkle(film_name_raw) => {
-            let film_name_input = film_name_raw.trim().to_lowercase();
-            info!("Attempting to mark film as watched: '{}'", film_name_input);
-            let all_films_in_master_list = load_films("filmler.txt");
-
-            let mut exact_match: Option<String> = None;
-            let mut potential_matches: Vec<String> = Vec::new();
-
-            for f in all_films_in_master_list.iter() {
-                let f_lower = f.to_lowercase();
-                if f_lower == film_name_input {
-                    exact_match = Some(f.clone());
-                    break;
-                } else if f_lower.contains(&film_name_input) {
-                    potential_matches.push(f.clone());
-                }
-            }
-
-            if let Some(film_to_mark) = exact_match {
-                mark_film_as_watched(&film_to_mark);
-                bot.send_message(
-                    msg.chat.id,
-                    format!("✅ '{}' filmi izlenenlere eklendi.", film_to_mark),
-                )
-                .await?;
-            } else {
-                if !potential_matches.is_empty() {
-                    let suggestions = potential_matches.join(", ");
-                    bot.send_message(
-                        msg.chat.id,
-                        format!(
-                            "Hata: '{}' adında bir film bulunamadı. Bunu mu demek istediniz: {}?",
-                            film_name_input, suggestions
-                        ),
-                    )
-                    .await?;
-                } else {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("Hata: '{}' adında bir film bulunamadı. Lütfen `filmler.txt` dosyasındaki tam adı (yıl bilgisi dahil) kullanın.", film_name_input)
-                    ).await?;
-                }
-            }
+        Command::FilmOner(arg) => {
+            suggest(&bot, &msg, &library, &recommender, &ns, Kind::Film, &arg).await?
+        }
+        Command::DiziOner(arg) => {
+            suggest(&bot, &msg, &library, &recommender, &ns, Kind::Series, &arg).await?
         }
 
-        Command::IzlenenDiziEkle(series_name_raw) => {
-            let series_name_input = series_name_raw.trim().to_lowercase();
-            info!(
-                "Attempting to mark series as watched: '{}'",
-                series_name_input
-            );
-            let all_series_in_master_list = load_series("diziler.txt");
-
-            let mut exact_match: Option<String> = None;
-            let mut potential_matches: Vec<String> = Vec::new();
-
-            for s in all_series_in_master_list.iter() {
-                let s_lower = s.to_lowercase();
-                if s_lower == series_name_input {
-                    exact_match = Some(s.clone());
-                    break;
-                } else if s_lower.contains(&series_name_input) {
-                    potential_matches.push(s.clone());
-                }
-            }
+        Command::IzlenenFilmEkle(name) => {
+            mark_watched(&bot, &msg, &library, &dialogue, &actor, Kind::Film, &name).await?
+        }
+        Command::IzlenenDiziEkle(name) => {
+            mark_watched(&bot, &msg, &library, &dialogue, &actor, Kind::Series, &name).await?
+        }
 
-            if let Some(series_to_mark) = exact_match {
-                mark_series_as_watched(&series_to_mark);
-                bot.send_message(
-                    msg.chat.id,
-                    format!("✅ '{}' dizisi izlenenlere eklendi.", series_to_mark),
-                )
-                .await?;
-            } else {
-                if !potential_matches.is_empty() {
-                    let suggestions = potential_matches.join(", ");
-                    bot.send_message(
-                        msg.chat.id,
-                        format!(
-                            "Hata: '{}' adında bir dizi bulunamadı. Bunu mu demek istediniz: {}?",
-                            series_name_input, suggestions
-                        ),
-                    )
-                    .await?;
-                } else {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("Hata: '{}' adında bir dizi bulunamadı. Lütfen `diziler.txt` dosyasındaki tam adı (sezon bilgisi dahil) kullanın.", series_name_input)
-                    ).await?;
-                }
-            }
+        Command::FilmEkle(name) => {
+            add_to_catalogue(&bot, &msg, &library, &actor, Kind::Film, &name).await?
+        }
+        Command::DiziEkle(name) => {
+            add_to_catalogue(&bot, &msg, &library, &actor, Kind::Series, &name).await?
         }
 
-        Command::FilmEkle(film_name_raw) => {
-            let film_name = film_name_raw.trim().to_string();
-            info!("Attempting to add film to master list: '{}'", film_name);
-            match add_film_to_file(&film_name) {
-                Ok(added) => {
-                    if added {
-                        bot.send_message(
-                            msg.chat.id,
-                            format!("✅ '{}' filmi `filmler.txt` dosyasına eklendi.", film_name),
-                        )
-                        .await?;
-                    } else {
-                        bot.send_message(
-                            msg.chat.id,
-                            format!(
-                                "ℹ️ Film '{}' zaten `filmler.txt` dosyasında mevcut.",
-                                film_name
-                            ),
-                        )
-                        .await?;
-                    }
-                }
-                Err(e) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("❌ Film eklenirken bir hata oluştu: {}", e),
-                    )
-                    .await?;
-                }
-            }
+        Command::FilmEtiket(arg) => {
+            set_tags(&bot, &msg, &library, &actor, Kind::Film, &arg).await?
+        }
+        Command::DiziEtiket(arg) => {
+            set_tags(&bot, &msg, &library, &actor, Kind::Series, &arg).await?
         }
 
-        Command::DiziEkle(series_name_raw) => {
-            let series_name = series_name_raw.trim().to_string();
-            info!("Attempting to add series to master list: '{}'", series_name);
-            match add_series_to_file(&series_name) {
-                Ok(added) => {
-                    if added {
-                        bot.send_message(
-                            msg.chat.id,
-                            format!(
-                                "✅ '{}' dizisi `diziler.txt` dosyasına eklendi.",
-                                series_name
-                            ),
-                        )
-                        .await?;
-                    } else {
-                        bot.send_message(
-                            msg.chat.id,
-                            format!(
-                                "ℹ️ Dizi '{}' zaten `diziler.txt` dosyasında mevcut.",
-                                series_name
-                            ),
-                        )
-                        .await?;
-                    }
-                }
-                Err(e) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("❌ Dizi eklenirken bir hata oluştu: {}", e),
-                    )
-                    .await?;
-                }
-            }
+        Command::FilmSil(name) => {
+            remove_from_catalogue(&bot, &msg, &library, &actor, Kind::Film, &name).await?
+        }
+        Command::DiziSil(name) => {
+            remove_from_catalogue(&bot, &msg, &library, &actor, Kind::Series, &name).await?
         }
+        Command::YenidenAdlandir(arg) => rename(&bot, &msg, &library, &actor, &arg).await?,
+        Command::Izlenmedi(name) => unwatch(&bot, &msg, &library, &actor, &name).await?,
+        Command::GeriAl(arg) => undo(&bot, &msg, &library, &actor, &arg).await?,
+        Command::Gunluk => list_journal(&bot, &msg, &library, &ns).await?,
 
-        Command::TumFilmler => {
-            let all_films = load_films("filmler.txt");
-            if all_films.is_empty() {
-                bot.send_message(msg.chat.id, "Henüz `filmler.txt` dosyasında kayıtlı bir film yok. `/film_ekle` komutunu kullanarak ekleyebilirsin.").await?;
-            } else {
-                let mut response_text = "🎬 Tüm Filmler:\n".to_string();
-                let mut sorted_films: Vec<&String> = all_films.iter().collect();
-                sorted_films.sort();
-                for film in sorted_films.iter() {
-                    response_text.push_str(&format!("- {}\n", film));
-                }
-                send_long_message(bot, msg.chat.id, response_text).await?;
-            }
+        Command::TumFilmler(order) => {
+            send_list(
+                &bot,
+                &msg,
+                &library,
+                &ns,
+                View::Catalogue,
+                Kind::Film,
+                &order,
+            )
+            .await?
+        }
+        Command::TumDiziler(order) => {
+            send_list(
+                &bot,
+                &msg,
+                &library,
+                &ns,
+                View::Catalogue,
+                Kind::Series,
+                &order,
+            )
+            .await?
         }
 
-        Command::TumDiziler => {
-            let all_series = load_series("diziler.txt");
-            if all_series.is_empty() {
-                bot.send_message(msg.chat.id, "Henüz `diziler.txt` dosyasında kayıtlı bir dizi yok. `/dizi_ekle` komutunu kullanarak ekleyebilirsin.").await?;
-            } else {
-                let mut response_text = "📺 Tüm Diziler:\n".to_string();
-                let mut sorted_series: Vec<&String> = all_series.iter().collect();
-                sorted_series.sort();
-                for series_name in sorted_series.iter() {
-                    response_text.push_str(&format!("- {}\n", series_name));
-                }
-                send_long_message(bot, msg.chat.id, response_text).await?;
-            }
+        Command::IzlenmemisFilmler(order) => {
+            send_list(
+                &bot,
+                &msg,
+                &library,
+                &ns,
+                View::Unwatched,
+                Kind::Film,
+                &order,
+            )
+            .await?
+        }
+        Command::IzlenmemisDiziler(order) => {
+            send_list(
+                &bot,
+                &msg,
+                &library,
+                &ns,
+                View::Unwatched,
+                Kind::Series,
+                &order,
+            )
+            .await?
         }
 
-        Command::IzlenmemisFilmler => {
-            let all_films = load_films("filmler.txt");
-            let watched_films = load_watched_films("izlenen_filmler.txt");
-            let mut unwatched_films: Vec<&String> = all_films
-                .iter()
-                .filter(|f| !watched_films.contains(f.as_str()))
-                .collect();
-            unwatched_films.sort();
+        Command::Ara(arg) => search(&bot, &msg, &library, &ns, &arg).await?,
 
-            if unwatched_films.is_empty() {
-                bot.send_message(msg.chat.id, "🎉 Harika! Tüm filmleri izlemişsin veya listen boş. Yeni filmler eklemek için `/film_ekle` komutunu kullanabilirsin.").await?;
-            } else {
-                let mut response_text = "🎬 İzlenmemiş Filmler:\n".to_string();
-                for film in unwatched_films.iter() {
-                    response_text.push_str(&format!("- {}\n", film));
-                }
-                send_long_message(bot, msg.chat.id, response_text).await?;
-            }
+        Command::IzlenenFilmler(order) => {
+            send_list(&bot, &msg, &library, &ns, View::Watched, Kind::Film, &order).await?
+        }
+        Command::IzlenenDiziler(order) => {
+            send_list(
+                &bot,
+                &msg,
+                &library,
+                &ns,
+                View::Watched,
+                Kind::Series,
+                &order,
+            )
+            .await?
         }
 
-        Command::IzlenmemisDiziler => {
-            let all_series = load_series("diziler.txt");
-            let watched_series = load_watched_series("izlenen_diziler.txt");
-            let mut unwatched_series: Vec<&String> = all_series
-                .iter()
-                .filter(|s| !watched_series.contains(s.as_str()))
-                .collect();
-            unwatched_series.sort();
+        Command::BolumIzledim(arg) => record_episode(&bot, &msg, &library, &actor, &arg).await?,
+        Command::SezonBitti(arg) => finish_season(&bot, &msg, &library, &actor, &arg).await?,
+        Command::Devam => list_progress(&bot, &msg, &library, &ns).await?,
 
-            if unwatched_series.is_empty() {
-                bot.send_message(msg.chat.id, "🎉 Harika! Tüm dizileri izlemişsin veya listen boş. Yeni diziler eklemek için `/dizi_ekle` komutunu kullanabilirsin.").await?;
-            } else {
-                let mut response_text = "📺 İzlenmemiş Diziler:\n".to_string();
-                for series_name in unwatched_series.iter() {
-                    response_text.push_str(&format!("- {}\n", series_name));
-                }
-                send_long_message(bot, msg.chat.id, response_text).await?;
-            }
+        Command::FilmDurum(arg) => {
+            set_status(&bot, &msg, &library, &actor, Kind::Film, &arg).await?
         }
-
-        Command::IzlenenFilmler => {
-            let watched_films = load_watched_films("izlenen_filmler.txt");
-            if watched_films.is_empty() {
-                bot.send_message(msg.chat.id, "Henüz izlenmiş bir film yok. `/film_oner` komutunu kullanarak ilk filmini öner!").await?;
-            } else {
-                let mut response_text = "🎬 İzlediğin Filmler:\n".to_string();
-                let mut sorted_films: Vec<&String> = watched_films.iter().collect();
-                sorted_films.sort();
-                for film in sorted_films.iter() {
-                    response_text.push_str(&format!("- {}\n", film));
-                }
-                send_long_message(bot, msg.chat.id, response_text).await?;
-            }
+        Command::DiziDurum(arg) => {
+            set_status(&bot, &msg, &library, &actor, Kind::Series, &arg).await?
         }
+        Command::Durumlar => list_statuses(&bot, &msg, &library, &ns).await?,
 
-        Command::IzlenenDiziler => {
-            let watched_series = load_watched_series("izlenen_diziler.txt");
-            if watched_series.is_empty() {
-                bot.send_message(msg.chat.id, "Henüz izlenmiş bir dizi yok. `/dizi_oner` komutunu kullanarak ilk dizini öner!").await?;
-            } else {
-                let mut response_text = "📺 İzlediğin Diziler:\n".to_string();
-                let mut sorted_series: Vec<&String> = watched_series.iter().collect();
-                sorted_series.sort();
-                for series_name in sorted_series.iter() {
-                    response_text.push_str(&format!("- {}\n", series_name));
-                }
-                send_long_message(bot, msg.chat.id, response_text).await?;
-            }
+        Command::Gecmis(page) => list_history(&bot, &msg, &library, &ns, &page).await?,
+
+        Command::Puanla(arg) => rate(&bot, &msg, &library, &actor, &arg).await?,
+        Command::EnIyiler => list_best(&bot, &msg, &library, &ns).await?,
+
+        Command::Istatistik => show_stats(&bot, &msg, &library, &ns).await?,
+        Command::Grafikler => send_charts(&bot, &msg, &library, &ns).await?,
+        Command::YilOzeti(arg) => year_in_review(&bot, &msg, &library, &ns, &arg).await?,
+        Command::DisaAktar(format) => export(&bot, &msg, &library, &ns, &format).await?,
+        Command::IceAktar => {
+            bot.send_message(msg.chat.id, IMPORT_HELP).await?;
         }
 
+        Command::HataliKayitlar => list_problems(&bot, &msg, &library).await?,
+
         Command::Merhaba => {
             let name = msg
                 .from
@@ -387,177 +473,2046 @@ async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
     Ok(())
 }
 
-async fn send_long_message(bot: Bot, chat_id: ChatId, text: String) -> ResponseResult<()> {
-    let lines: Vec<&str> = text.lines().collect();
-    let mut current_chunk = String::new();
-
-    for line in lines {
-        if current_chunk.len() + line.len() + 1 > MAX_MESSAGE_LENGTH {
-            if !current_chunk.is_empty() {
-                bot.send_message(chat_id, current_chunk.clone()).await?;
-                current_chunk.clear();
+async fn suggest(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    recommender: &Recommender,
+    ns: &Namespace,
+    kind: Kind,
+    arg: &str,
+) -> ResponseResult<()> {
+    let years = match kind {
+        Kind::Film => " [2010-2020]",
+        Kind::Series => "",
+    };
+    let usage = format!(
+        "Kullanım: /{}_oner [adet] [devam] [etiket...]{}",
+        kind.key(),
+        years
+    );
+    let mut prefer_in_progress = false;
+    let mut count = 1;
+    let mut rest: Vec<&str> = Vec::new();
+    for word in arg.split_whitespace() {
+        if word.to_lowercase() == "devam" {
+            prefer_in_progress = true;
+        } else if word.len() < 4 && word.bytes().all(|b| b.is_ascii_digit()) {
+            // Four digits are a year for the filter; shorter numbers a count.
+            match word.parse() {
+                Ok(n) if (1..=MAX_SUGGESTIONS).contains(&n) => count = n,
+                _ => {
+                    bot.send_message(
+                        msg.chat.id,
+                        format!(
+                            "Hata: 1 ile {} arasında öneri isteyebilirsin.\n{}",
+                            MAX_SUGGESTIONS, usage
+                        ),
+                    )
+                    .await?;
+                    return Ok(());
+                }
             }
+        } else {
+            rest.push(word);
         }
-        current_chunk.push_str(line);
-        current_chunk.push('\n');
     }
+    let filter = match TitleFilter::parse(&rest.join(" ")) {
+        // Series have no year to match against.
+        Ok(filter) if kind == Kind::Series && filter.years.is_some() => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Hata: dizilerin yılı kayıtlı değil, yıl aralığı yalnızca filmleri daraltır.\n{}",
+                    usage
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+        Ok(filter) => filter,
+        Err(problem) => {
+            bot.send_message(msg.chat.id, format!("Hata: {}.\n{}", problem, usage))
+                .await?;
+            return Ok(());
+        }
+    };
+    let wanted = Wanted {
+        filter,
+        prefer_in_progress,
+    };
+    let titles =
+        match get_random_unwatched(library, recommender, msg.chat.id, ns, kind, &wanted, count) {
+            Ok(titles) => titles,
+            Err(e) => return report_storage_error(bot, msg, e).await,
+        };
 
-    if !current_chunk.is_empty() {
-        bot.send_message(chat_id, current_chunk).await?;
+    if titles.is_empty() {
+        bot.send_message(msg.chat.id, no_suggestion_text(kind, &wanted.filter))
+            .await?;
+        return Ok(());
+    }
+    if titles.len() < count {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Önerebileceğim yalnızca {} {} kaldı.",
+                titles.len(),
+                kind.key()
+            ),
+        )
+        .await?;
+    }
+    for title in titles {
+        bot.send_message(msg.chat.id, suggestion_text(kind, &title, &wanted))
+            .reply_markup(suggestion_keyboard(kind, &title))
+            .await?;
     }
     Ok(())
 }
 
-fn load_films(path: &str) -> Vec<String> {
-    fs::read_to_string(path)
-        .unwrap_or_else(|_| {
-            info!(
-                "'{}' dosyası bulunamadı veya okunamadı. Boş liste döndürüldü.",
-                path
-            );
-            String::new()
-        })
-        .lines()
-        .filter(|s| !s.trim().is_empty())
-        .map(|s| s.trim().to_string())
-        .collect()
+fn suggestion_text(kind: Kind, title: &Title, wanted: &Wanted) -> String {
+    let text = format!("{} {} Önerisi: {}", kind.emoji(), kind.title(), title);
+    match wanted.caption() {
+        Some(caption) => format!("{}\n{}", text, caption),
+        None => text,
+    }
 }
 
-fn get_random_unwatched_film() -> Option<String> {
-    let all_films = load_films("filmler.txt");
-    let watched = load_watched_films("izlenen_filmler.txt");
-    let unwatched: Vec<_> = all_films
-        .iter()
-        .filter(|f| !watched.contains(f.as_str()))
-        .cloned()
-        .collect();
-
-    let mut rng = rand::thread_rng();
-    unwatched.choose(&mut rng).cloned()
+fn no_suggestion_text(kind: Kind, filter: &TitleFilter) -> String {
+    if filter.is_empty() {
+        all_suggested_text(kind)
+    } else {
+        format!(
+            "🔍 '{}' filtresine uyan izlenmemiş bir {} kalmadı.",
+            filter,
+            kind.key()
+        )
+    }
 }
 
-fn load_watched_films(path: &str) -> HashSet<String> {
-    match fs::read_to_string(path) {
-        Ok(contents) => contents.lines().map(str::to_string).collect(),
-        Err(_) => HashSet::new(),
-    }
+fn all_suggested_text(kind: Kind) -> String {
+    format!(
+        "✅ Tüm {} önerildi! `/{}_ekle` ile yenilerini ekleyebilir, `/izlenmedi` ile birini listene geri alabilirsin.",
+        kind.plural(),
+        kind.key()
+    )
 }
 
-fn mark_film_as_watched(film: &str) {
-    let mut watched_films = load_watched_films("izlenen_filmler.txt");
-    if watched_films.insert(film.to_string()) {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open("izlenen_filmler.txt")
-            .expect("❌ izlenen_filmler.txt dosyasına yazılamıyor!");
+/// Handles the buttons under a suggestion. The message is edited in place so
+/// the chat shows what became of each suggestion.
+async fn answer_button(
+    bot: Bot,
+    query: CallbackQuery,
+    library: Library,
+    recommender: Recommender,
+) -> ResponseResult<()> {
+    let data = query.data.as_deref().and_then(ButtonData::parse);
+    let (Some(data), Some(actor), Some(message)) =
+        (data, Actor::from_callback(&query), query.regular_message())
+    else {
+        bot.answer_callback_query(query.id.clone())
+            .text("Bu düğme artık geçerli değil.")
+            .await?;
+        return Ok(());
+    };
+    let (chat_id, message_id, kind) = (message.chat.id, message.id, data.kind);
+    let ns = actor.ns;
+
+    let Some(title) = library
+        .catalogue(kind)
+        .into_iter()
+        .find(|t| title_id(t) == data.title_id)
+    else {
+        bot.answer_callback_query(query.id.clone())
+            .text(format!("Bu {} artık katalogda değil.", kind.key()))
+            .await?;
+        bot.edit_message_reply_markup(chat_id, message_id).await?;
+        return Ok(());
+    };
 
-        let mut sorted_films: Vec<&String> = watched_films.iter().collect();
-        sorted_films.sort();
-        for f in sorted_films {
-            writeln!(file, "{}", f).expect("❌ Filme yazma başarısız!");
+    let outcome = match data.action {
+        Action::Watched => {
+            let marked = title.to_string();
+            blocking(&library, move |library| {
+                library.mark_watched(&actor, kind, &marked)
+            })
+            .await
+            .map(|_| {
+                info!("'{}' marked as watched from a button by {}.", title, ns);
+                (
+                    format!("✅ '{}' {} izlenenlere eklendi.", title, kind.possessive()),
+                    None,
+                )
+            })
+        }
+        Action::Later => {
+            let planned = title.clone();
+            blocking(&library, move |library| {
+                library.set_status(&actor, kind, &planned, Some(Status::Planned))
+            })
+            .await
+            .map(|()| {
+                (
+                    format!(
+                        "{} '{}' sonra izlemek üzere planlandı.",
+                        Status::Planned,
+                        title
+                    ),
+                    None,
+                )
+            })
+        }
+        Action::Another => {
+            let wanted = message.text().map(Wanted::from_text).unwrap_or_default();
+            get_random_unwatched(&library, &recommender, chat_id, &ns, kind, &wanted, 1).map(
+                |titles| match titles.first() {
+                    Some(next) => (
+                        suggestion_text(kind, next, &wanted),
+                        Some(suggestion_keyboard(kind, next)),
+                    ),
+                    None => (no_suggestion_text(kind, &wanted.filter), None),
+                },
+            )
+        }
+    };
+
+    match outcome {
+        Ok((text, keyboard)) => {
+            bot.answer_callback_query(query.id.clone()).await?;
+            // Leaving out the keyboard removes the buttons.
+            let edit = bot.edit_message_text(chat_id, message_id, text);
+            match keyboard {
+                Some(keyboard) => edit.reply_markup(keyboard).await?,
+                None => edit.await?,
+            };
+        }
+        Err(e) => {
+            error!(
+                "Storage error while handling a button in chat {}: {}",
+                chat_id, e
+            );
+            bot.answer_callback_query(query.id.clone())
+                .text(format!("❌ Bir hata oluştu: {}", e))
+                .await?;
         }
-        info!("Successfully marked film '{}' as watched.", film);
-    } else {
-        info!("Film '{}' zaten izlenenler listesinde.", film);
     }
+    Ok(())
 }
 
-fn add_film_to_file(film: &str) -> io::Result<bool> {
-    let mut all_films = load_films("filmler.txt")
-        .into_iter()
-        .collect::<HashSet<String>>();
-    if all_films.insert(film.to_string()) {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open("filmler.txt")?;
-
-        let mut sorted_films: Vec<&String> = all_films.iter().collect();
-        sorted_films.sort();
-        for f in sorted_films {
-            writeln!(file, "{}", f)?;
-        }
-        info!("Film '{}' filmler.txt dosyasına eklendi.", film);
-        Ok(true)
-    } else {
-        info!("Film '{}' zaten filmler.txt dosyasında mevcut.", film);
-        Ok(false)
+async fn mark_watched(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    dialogue: &PickDialogue,
+    actor: &Actor,
+    kind: Kind,
+    name_raw: &str,
+) -> ResponseResult<()> {
+    info!(
+        "Attempting to mark {} as watched: '{}'",
+        kind.key(),
+        name_raw.trim()
+    );
+    let input = split_rating_arg(name_raw);
+    let rating = match input.rating() {
+        Ok(rating) => rating,
+        Err(problem) => {
+            bot.send_message(msg.chat.id, format!("Hata: {}", problem))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if input.name.trim().is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            format!("Hata: bir {} adı yazmalısın.", kind.key()),
+        )
+        .await?;
+        return Ok(());
     }
-}
 
-fn load_series(path: &str) -> HashSet<String> {
-    fs::read_to_string(path)
-        .unwrap_or_else(|_| {
-            info!(
-                "'{}' dosyası bulunamadı veya okunamadı. Boş liste döndürüldü.",
-                path
+    let title = match library.lookup(kind, input.name) {
+        Lookup::Ambiguous(candidates, more) => {
+            let mut question = format!(
+                "'{}' için birden fazla {} buldum. Hangisini izledin?",
+                input.name.trim(),
+                kind.key()
             );
-            String::new()
-        })
-        .lines()
-        .filter(|s| !s.trim().is_empty())
-        .map(|s| s.trim().to_string())
-        .collect()
+            if more > 0 {
+                question.push_str(&format!(
+                    "\n{} sonuçtan en yakın {} tanesi aşağıda; aradığın yoksa adı daha uzun yaz.",
+                    candidates.len() + more,
+                    candidates.len()
+                ));
+            }
+            let sent = bot
+                .send_message(msg.chat.id, question)
+                .reply_markup(choice_keyboard(&candidates))
+                .await?;
+            let mut state = dialogue.get_or_default().await.unwrap_or_else(|e| {
+                error!(
+                    "Failed to read the pending choices of chat {}: {}",
+                    msg.chat.id, e
+                );
+                PickState::default()
+            });
+            state.ask(Pending {
+                user: actor.user,
+                ns: actor.ns,
+                kind,
+                candidates,
+                rating,
+                message_id: sent.id,
+            });
+            if let Err(e) = dialogue.update(state).await {
+                error!(
+                    "Failed to store the pending choice for chat {}: {}",
+                    msg.chat.id, e
+                );
+            }
+            return Ok(());
+        }
+        lookup => report_lookup(bot, msg, kind, input.name, lookup).await?,
+    };
+    let Some(title) = title else {
+        return Ok(());
+    };
+
+    let (by, marked) = (*actor, title.clone());
+    let reply = blocking(library, move |library| {
+        mark_watched_reply(library, &by, kind, &marked, rating)
+    });
+    match reply.await {
+        Ok(reply) => {
+            bot.send_message(msg.chat.id, reply).await?;
+        }
+        Err(e) => {
+            error!("Failed to mark '{}' as watched: {}", title, e);
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "❌ {} izlenenlere eklenirken bir hata oluştu: {}",
+                    kind.title(),
+                    e
+                ),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Marks a resolved title as watched, stores the rating given with it and
+/// returns the reply to show.
+fn mark_watched_reply(
+    library: &Library,
+    actor: &Actor,
+    kind: Kind,
+    title: &Title,
+    rating: Option<Rating>,
+) -> io::Result<String> {
+    let ns = &actor.ns;
+    let to_mark = title.to_string();
+    let times = library.mark_watched(actor, kind, &to_mark)?;
+    let mut reply = if times == 1 {
+        info!(
+            "Successfully marked {} '{}' as watched for {}.",
+            kind.key(),
+            to_mark,
+            ns
+        );
+        format!(
+            "✅ '{}' {} izlenenlere eklendi.",
+            to_mark,
+            kind.possessive()
+        )
+    } else {
+        info!(
+            "{} '{}' watched again by {} ({} times).",
+            kind.title(),
+            to_mark,
+            ns,
+            times
+        );
+        format!(
+            "🔁 '{}' {} tekrar izlendi, bu {}. izleyişin.",
+            to_mark,
+            kind.possessive(),
+            times
+        )
+    };
+    if let Some(rating) = rating {
+        if let Some(stored) = library.rate(actor, kind, title, rating)? {
+            reply.push_str(&format!("\n{}", stored));
+        }
+    }
+    Ok(reply)
+}
+
+/// Handles the buttons offered when `/izlenen_*_ekle` matched several titles.
+async fn answer_choice(
+    bot: Bot,
+    query: CallbackQuery,
+    choice: Choice,
+    library: Library,
+    dialogue: PickDialogue,
+    mut state: PickState,
+) -> ResponseResult<()> {
+    let Some(message) = query.regular_message() else {
+        bot.answer_callback_query(query.id.clone()).await?;
+        return Ok(());
+    };
+    let Some(question) = state.find(message.id).cloned() else {
+        return expire_choice(&bot, &query, message).await;
+    };
+    let Some(actor) = Actor::from_callback(&query).filter(|a| a.ns == question.ns) else {
+        bot.answer_callback_query(query.id.clone())
+            .text("Bu seçimi komutu yazan kişi yapabilir.")
+            .await?;
+        return Ok(());
+    };
+
+    let text = match choice {
+        Choice::Cancel => "Vazgeçildi.".to_string(),
+        Choice::Index(i) => {
+            let Some(title) = question.candidates.get(i) else {
+                return expire_choice(&bot, &query, message).await;
+            };
+            let (kind, marked, rating) = (question.kind, title.clone(), question.rating.clone());
+            let reply = blocking(&library, move |library| {
+                mark_watched_reply(library, &actor, kind, &marked, rating)
+            });
+            match reply.await {
+                Ok(reply) => reply,
+                Err(e) => {
+                    error!("Failed to mark '{}' as watched: {}", title, e);
+                    bot.answer_callback_query(query.id.clone())
+                        .text(format!("❌ Bir hata oluştu: {}", e))
+                        .await?;
+                    return Ok(());
+                }
+            }
+        }
+    };
+    state.close(message.id);
+    let stored = if state.is_empty() {
+        dialogue.exit().await
+    } else {
+        dialogue.update(state).await
+    };
+    if let Err(e) = stored {
+        error!(
+            "Failed to clear the pending choice for chat {}: {}",
+            message.chat.id, e
+        );
+    }
+    bot.answer_callback_query(query.id.clone()).await?;
+    bot.edit_message_text(message.chat.id, message.id, text)
+        .await?;
+    Ok(())
+}
+
+/// The buttons belong to a question that was answered or replaced since.
+async fn expire_choice(bot: &Bot, query: &CallbackQuery, message: &Message) -> ResponseResult<()> {
+    bot.answer_callback_query(query.id.clone())
+        .text("Bu seçim artık geçerli değil, komutu yeniden yaz.")
+        .await?;
+    bot.edit_message_reply_markup(message.chat.id, message.id)
+        .await?;
+    Ok(())
+}
+
+/// Resolves user input to a catalogue entry, replying with suggestions or an
+/// error when there is no single match.
+async fn resolve_title(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    kind: Kind,
+    name_raw: &str,
+) -> ResponseResult<Option<Title>> {
+    if name_raw.trim().is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            format!("Hata: bir {} adı yazmalısın.", kind.key()),
+        )
+        .await?;
+        return Ok(None);
+    }
+    let lookup = library.lookup(kind, name_raw);
+    report_lookup(bot, msg, kind, name_raw, lookup).await
+}
+
+/// Replies to a lookup that did not find exactly one title.
+async fn report_lookup(
+    bot: &Bot,
+    msg: &Message,
+    kind: Kind,
+    name_raw: &str,
+    lookup: Lookup,
+) -> ResponseResult<Option<Title>> {
+    let name_input = name_raw.trim().to_lowercase();
+    match lookup {
+        Lookup::Found(title) => return Ok(Some(title)),
+        Lookup::Ambiguous(candidates, more) => {
+            let mut suggestions = candidates
+                .iter()
+                .map(Title::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            if more > 0 {
+                suggestions.push_str(&format!(" (ve {} tane daha; adı daha uzun yaz)", more));
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Hata: '{}' adında bir {} bulunamadı. Bunu mu demek istediniz: {}?",
+                    name_input,
+                    kind.key(),
+                    suggestions
+                ),
+            )
+            .await?;
+        }
+        Lookup::NotFound => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Hata: '{}' adında bir {} bulunamadı. Lütfen katalogdaki tam adı ({} bilgisi dahil) kullanın.",
+                    name_input,
+                    kind.key(),
+                    kind.detail()
+                ),
+            )
+            .await?;
+        }
+    }
+    Ok(None)
+}
+
+async fn record_episode(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    arg: &str,
+) -> ResponseResult<()> {
+    let ns = &actor.ns;
+    let Some((name, episode)) = split_episode_arg(arg).filter(|(_, e)| e.episode > 0) else {
+        bot.send_message(
+            msg.chat.id,
+            "Kullanım: `/bolum_izledim <Dizi Adı> S02E05` (sezon ve bölüm numarası ile).",
+        )
+        .await?;
+        return Ok(());
+    };
+    let Some(series) = resolve_title(bot, msg, library, Kind::Series, name).await? else {
+        return Ok(());
+    };
+    if let Some(last) = series.seasons.filter(|&last| episode.season > last) {
+        bot.send_message(
+            msg.chat.id,
+            format!("Hata: '{}' dizisinin {} sezonu var.", series, last),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (by, recorded) = (*actor, series.clone());
+    let change = blocking(library, move |library| {
+        library.record_episode(&by, &recorded, episode)
+    });
+    match change.await {
+        Ok(()) => {
+            info!("Recorded {} of '{}' for {}.", episode, series, ns);
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ '{}' için {} kaydedildi. Sıradaki bölüm: {}.",
+                    series,
+                    episode,
+                    episode.up_next(series.seasons)
+                ),
+            )
+            .await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
+}
+
+async fn finish_season(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    arg: &str,
+) -> ResponseResult<()> {
+    let ns = &actor.ns;
+    let (name, season) = split_season_arg(arg);
+    if name.is_empty() {
+        bot.send_message(msg.chat.id, "Kullanım: `/sezon_bitti <Dizi Adı> [sezon]`")
+            .await?;
+        return Ok(());
+    }
+    let Some(series) = resolve_title(bot, msg, library, Kind::Series, name).await? else {
+        return Ok(());
+    };
+
+    // Without an explicit season, finish the one currently being watched.
+    let season = match season {
+        Some(season) => Some(season),
+        None => match library.progress(ns) {
+            Ok(progress) => progress
+                .into_iter()
+                .find(|(t, _)| *t == series)
+                .map(|(_, episode)| episode.season),
+            Err(e) => return report_storage_error(bot, msg, e).await,
+        },
+    };
+    let Some(season) = season.or(series.seasons.filter(|&n| n == 1)) else {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Hangi sezonu bitirdiğini yazmalısın, örn: `/sezon_bitti {} 2`",
+                series.name
+            ),
+        )
+        .await?;
+        return Ok(());
+    };
+    if season == 0 || series.seasons.is_some_and(|last| season > last) {
+        bot.send_message(
+            msg.chat.id,
+            format!("Hata: '{}' dizisinin {}. sezonu yok.", series, season),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (by, finished) = (*actor, series.clone());
+    let change = blocking(library, move |library| {
+        library.finish_season(&by, &finished, season)
+    });
+    match change.await {
+        Ok(SeasonOutcome::NextSeason(next)) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ '{}' {}. sezon tamamlandı. Sıradaki bölüm: {}.",
+                    series,
+                    season,
+                    Episode {
+                        season: next,
+                        episode: 1
+                    }
+                ),
+            )
+            .await?;
+        }
+        Ok(SeasonOutcome::Completed) => {
+            info!("'{}' completed by {}.", series, ns);
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "🎉 '{}' dizisinin son sezonunu bitirdin, dizi izlenenlere eklendi.",
+                    series
+                ),
+            )
+            .await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
+}
+
+async fn list_progress(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+) -> ResponseResult<()> {
+    let progress = match library.progress(ns) {
+        Ok(progress) => progress,
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    if progress.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Şu anda devam ettiğin bir dizi yok. `/bolum_izledim <Dizi Adı> S01E01` ile başlayabilirsin.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut response_text = "▶️ Devam Ettiğin Diziler:\n".to_string();
+    for (series, episode) in progress {
+        response_text.push_str(&format!(
+            "- {}: sıradaki {}\n",
+            series,
+            episode.up_next(series.seasons)
+        ));
+    }
+    send_long_message(bot.clone(), msg.chat.id, response_text).await?;
+    Ok(())
+}
+
+async fn add_to_catalogue(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    kind: Kind,
+    name_raw: &str,
+) -> ResponseResult<()> {
+    let name = name_raw.trim().to_string();
+    info!(
+        "Attempting to add {} to master list: '{}'",
+        kind.key(),
+        name
+    );
+    let (by, line) = (*actor, name.clone());
+    match blocking(library, move |library| library.add(&by, kind, &line)).await {
+        Ok(added) => {
+            if added {
+                info!(
+                    "{} '{}' {} listesine eklendi.",
+                    kind.title(),
+                    name,
+                    kind.plural()
+                );
+                let mut reply = format!("✅ '{}' {} kataloğa eklendi.", name, kind.possessive());
+                if let Some(problem) = Title::parse(&name).problem(kind) {
+                    reply.push_str(&format!("\n⚠️ Dikkat: {}.", problem));
+                }
+                bot.send_message(msg.chat.id, reply).await?;
+            } else {
+                info!(
+                    "{} '{}' zaten {} listesinde mevcut.",
+                    kind.title(),
+                    name,
+                    kind.plural()
+                );
+                bot.send_message(
+                    msg.chat.id,
+                    format!("ℹ️ {} '{}' zaten katalogda mevcut.", kind.title(), name),
+                )
+                .await?;
+            }
+        }
+        Err(e) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("❌ {} eklenirken bir hata oluştu: {}", kind.title(), e),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn remove_from_catalogue(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    kind: Kind,
+    name_raw: &str,
+) -> ResponseResult<()> {
+    if name_raw.trim().is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            format!("Kullanım: `/{}_sil <{} Adı>`", kind.key(), kind.title()),
+        )
+        .await?;
+        return Ok(());
+    }
+    let Some(title) = resolve_title(bot, msg, library, kind, name_raw).await? else {
+        return Ok(());
+    };
+
+    let (by, removed) = (*actor, title.clone());
+    match blocking(library, move |library| library.remove(&by, kind, &removed)).await {
+        Ok(true) => {
+            info!("{} '{}' removed from the catalogue.", kind.title(), title);
+            bot.send_message(
+                msg.chat.id,
+                format!("🗑️ '{}' {} katalogdan silindi.", title, kind.possessive()),
+            )
+            .await?;
+        }
+        Ok(false) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Hata: '{}' artık {} kataloğunda değil.", title, kind.key()),
+            )
+            .await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
+}
+
+async fn rename(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    arg: &str,
+) -> ResponseResult<()> {
+    let Some((old, new)) = arg
+        .split_once('|')
+        .map(|(old, new)| (old.trim(), new.trim()))
+        .filter(|(old, new)| !old.is_empty() && !new.is_empty())
+    else {
+        bot.send_message(
+            msg.chat.id,
+            "Kullanım: `/yeniden_adlandir [film|dizi] <Eski Ad> | <Yeni Ad>`, örn: `/yeniden_adlandir Iron Men (2008) | Iron Man (2008)`",
+        )
+        .await?;
+        return Ok(());
+    };
+    let Some((kind, title)) = resolve_any_title(bot, msg, library, old).await? else {
+        return Ok(());
+    };
+
+    let (by, renamed, new_line) = (*actor, title.clone(), new.to_string());
+    let change = blocking(library, move |library| {
+        library.rename(&by, kind, &renamed, &new_line)
+    });
+    match change.await {
+        Ok(RenameOutcome::Renamed(renamed)) => {
+            let mut reply = format!("✅ '{}' adı '{}' olarak değiştirildi.", title, renamed);
+            if let Some(problem) = renamed.problem(kind) {
+                reply.push_str(&format!("\n⚠️ Dikkat: {}.", problem));
+            }
+            bot.send_message(msg.chat.id, reply).await?;
+        }
+        Ok(RenameOutcome::Exists(existing)) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Hata: '{}' zaten {} kataloğunda var.", existing, kind.key()),
+            )
+            .await?;
+        }
+        Ok(RenameOutcome::Missing) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Hata: '{}' artık {} kataloğunda değil.", title, kind.key()),
+            )
+            .await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
+}
+
+async fn unwatch(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    name_raw: &str,
+) -> ResponseResult<()> {
+    let ns = &actor.ns;
+    if name_raw.trim().is_empty() {
+        bot.send_message(msg.chat.id, "Kullanım: `/izlenmedi [film|dizi] <Ad>`")
+            .await?;
+        return Ok(());
+    }
+    let Some((kind, title)) = resolve_any_title(bot, msg, library, name_raw).await? else {
+        return Ok(());
+    };
+
+    let (by, unwatched) = (*actor, title.clone());
+    let change = blocking(library, move |library| {
+        library.unwatch(&by, kind, &unwatched)
+    });
+    match change.await {
+        Ok(true) => {
+            info!("'{}' taken off the watched list of {}.", title, ns);
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "↩️ '{}' {} izlenenlerden çıkarıldı.",
+                    title,
+                    kind.possessive()
+                ),
+            )
+            .await?;
+        }
+        Ok(false) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("ℹ️ '{}' zaten izlediklerin arasında değil.", title),
+            )
+            .await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
 }
 
-fn load_watched_series(path: &str) -> HashSet<String> {
-    match fs::read_to_string(path) {
-        Ok(contents) => contents.lines().map(str::to_string).collect(),
-        Err(_) => HashSet::new(),
+async fn undo(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    arg: &str,
+) -> ResponseResult<()> {
+    let count = match arg.trim() {
+        "" => 1,
+        count => match count.parse::<usize>() {
+            Ok(count) if (1..=MAX_UNDO).contains(&count) => count,
+            _ => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "Kullanım: `/geri_al [adet]`, en fazla {} işlem geri alınabilir.",
+                        MAX_UNDO
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+        },
+    };
+    let by = *actor;
+    let undone = match blocking(library, move |library| library.undo(&by, count)).await {
+        Ok(undone) => undone,
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    if undone.is_empty() {
+        bot.send_message(msg.chat.id, "ℹ️ Geri alınacak bir işlemin yok.")
+            .await?;
+        return Ok(());
+    }
+    let mut response_text = String::from("↩️ Geri alındı:\n");
+    for change in journal::changes(&undone) {
+        response_text.push_str(&format!("- {}\n", journal::describe(change)));
+    }
+    bot.send_message(msg.chat.id, response_text).await?;
+    Ok(())
+}
+
+/// The latest journal entries of the namespace, with who made each change.
+async fn list_journal(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+) -> ResponseResult<()> {
+    let journal = match library.journal(ns) {
+        Ok(journal) => journal,
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    if journal.is_empty() {
+        bot.send_message(msg.chat.id, "Bu listede henüz kayıtlı bir değişiklik yok.")
+            .await?;
+        return Ok(());
+    }
+    let mut response_text = String::from("📒 Son Değişiklikler:\n");
+    for change in journal::changes(&journal).rev().take(HISTORY_PAGE_SIZE) {
+        let entry = &change[0];
+        let user = entry
+            .user
+            .map_or_else(|| "?".to_string(), |id| id.to_string());
+        response_text.push_str(&format!(
+            "- #{} {} 👤 {}: {}\n",
+            entry.id,
+            entry.local_date(),
+            user,
+            journal::describe(change)
+        ));
     }
+    bot.send_message(msg.chat.id, response_text).await?;
+    Ok(())
 }
 
-fn get_random_unwatched_series() -> Option<String> {
-    let all_series = load_series("diziler.txt");
-    let watched = load_watched_series("izlenen_diziler.txt");
-    let unwatched: Vec<_> = all_series
+async fn set_tags(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    kind: Kind,
+    arg: &str,
+) -> ResponseResult<()> {
+    let Some((name, tags)) = arg
+        .split_once('|')
+        .filter(|(name, _)| !name.trim().is_empty())
+    else {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Kullanım: `/{}_etiket <{} Adı> | etiket1, etiket2`",
+                kind.key(),
+                kind.title()
+            ),
+        )
+        .await?;
+        return Ok(());
+    };
+    let Some(title) = resolve_title(bot, msg, library, kind, name).await? else {
+        return Ok(());
+    };
+
+    let (by, tagged, tags) = (*actor, title.clone(), parse_tags(tags));
+    let change = blocking(library, move |library| {
+        library.set_tags(&by, kind, &tagged, tags)
+    });
+    match change.await {
+        Ok(Some(updated)) if updated.tags.is_empty() => {
+            bot.send_message(msg.chat.id, format!("✅ '{}' etiketleri silindi.", updated))
+                .await?;
+        }
+        Ok(Some(updated)) => {
+            info!("Tags of '{}' set to {:?}.", updated, updated.tags);
+            bot.send_message(
+                msg.chat.id,
+                format!("✅ '{}' etiketleri: {}", updated, updated.tags.join(", ")),
+            )
+            .await?;
+        }
+        Ok(None) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("Hata: '{}' artık {} kataloğunda değil.", title, kind.key()),
+            )
+            .await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
+}
+
+async fn search(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+    arg: &str,
+) -> ResponseResult<()> {
+    let query = match SearchQuery::parse(arg) {
+        Ok(query) => query,
+        Err(problem) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Hata: {}.\nKullanım: `/ara <sorgu> [tur:film|dizi] [yil:2019] [izlendi:evet|hayir]`",
+                    problem
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    // The header is read back when paging, so it has to stay on one line.
+    let arg = arg.split_whitespace().collect::<Vec<_>>().join(" ");
+    let data = PageData {
+        view: View::Search,
+        kind: Kind::Film,
+        order: ListOrder::Name,
+        ns: *ns,
+        page: 1,
+    };
+    let (header, lines) = match list_lines(library, &data, &arg) {
+        Ok(list) => list,
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    if !lines.is_empty() {
+        let (text, keyboard) = list_page(data, &header, &lines);
+        let request = bot.send_message(msg.chat.id, text);
+        match keyboard {
+            Some(keyboard) => request.reply_markup(keyboard).await?,
+            None => request.await?,
+        };
+        return Ok(());
+    }
+
+    let text = query.text();
+    let mut reply = format!("🔍 '{}' için bir sonuç bulunamadı.", arg);
+    let mut close: Vec<(Kind, matching::Match)> = Kind::ALL
+        .into_iter()
+        .filter(|k| query.kind.is_none_or(|q| q == *k))
+        .flat_map(|kind| {
+            matching::rank(&text, &library.catalogue(kind), SEARCH_SUGGESTIONS)
+                .into_iter()
+                .map(move |m| (kind, m))
+        })
+        .collect();
+    close.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+    close.truncate(SEARCH_SUGGESTIONS);
+    if !close.is_empty() {
+        reply.push_str("\nBunlardan birini mi arıyordun?");
+        for (kind, m) in close {
+            reply.push_str(&format!("\n- {} {}", kind.emoji(), m.title));
+        }
+    }
+    bot.send_message(msg.chat.id, reply).await?;
+    Ok(())
+}
+
+/// What a search result page searched for, read back from its header.
+fn searched_text(message: &str) -> Option<&str> {
+    let header = message.lines().next()?.strip_prefix("🔎 '")?;
+    Some(header.rsplit_once("' için ")?.0)
+}
+
+/// Every catalogue entry `arg` finds, closest names first; without free
+/// words, alphabetically.
+fn search_lines(library: &Library, ns: &Namespace, arg: &str) -> io::Result<Vec<String>> {
+    let query = SearchQuery::parse(arg).map_err(io::Error::other)?;
+    let mut hits: Vec<(Kind, Title, bool)> = Vec::new();
+    for kind in Kind::ALL {
+        if query.kind.is_some_and(|q| q != kind) {
+            continue;
+        }
+        let watched = library.watched(ns, kind)?;
+        for title in library.catalogue(kind) {
+            let seen = watched.contains(&title.to_string());
+            if query.matches(kind, &title, seen) {
+                hits.push((kind, title, seen));
+            }
+        }
+    }
+
+    let text = query.text();
+    hits.sort_by(|a, b| {
+        matching::score(&text, &b.1)
+            .total_cmp(&matching::score(&text, &a.1))
+            .then_with(|| a.1.name.cmp(&b.1.name))
+    });
+    Ok(hits
         .iter()
-        .filter(|s| !watched.contains(s.as_str()))
-        .cloned()
+        .map(|(kind, title, seen)| {
+            let mark = if *seen { "✅" } else { "⬜" };
+            let mut line = format!("{} {} {}", mark, kind.emoji(), title);
+            if !title.tags.is_empty() {
+                line.push_str(&format!(" 🏷️ {}", title.tags.join(", ")));
+            }
+            line
+        })
+        .collect())
+}
+
+/// Sends the first page of a list; the buttons under it page through the
+/// rest by editing the same message.
+async fn send_list(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+    view: View,
+    kind: Kind,
+    order: &str,
+) -> ResponseResult<()> {
+    let Some(order) = parse_order(bot, msg, order).await? else {
+        return Ok(());
+    };
+    let data = PageData {
+        view,
+        kind,
+        order,
+        ns: *ns,
+        page: 1,
+    };
+    let (header, lines) = match list_lines(library, &data, "") {
+        Ok(list) => list,
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    if lines.is_empty() {
+        bot.send_message(msg.chat.id, empty_list_text(view, kind))
+            .await?;
+        return Ok(());
+    }
+    let (text, keyboard) = list_page(data, &header, &lines);
+    let request = bot.send_message(msg.chat.id, text);
+    match keyboard {
+        Some(keyboard) => request.reply_markup(keyboard).await?,
+        None => request.await?,
+    };
+    Ok(())
+}
+
+/// Handles the previous and next buttons under a list.
+async fn answer_page(
+    bot: Bot,
+    query: CallbackQuery,
+    data: PageData,
+    library: Library,
+) -> ResponseResult<()> {
+    let Some(message) = query.regular_message() else {
+        bot.answer_callback_query(query.id.clone()).await?;
+        return Ok(());
+    };
+    if Namespace::from_callback(&query) != Some(data.ns) {
+        bot.answer_callback_query(query.id.clone())
+            .text("Bu listenin sayfalarını komutu yazan kişi çevirebilir.")
+            .await?;
+        return Ok(());
+    }
+    let searched = message.text().and_then(searched_text).unwrap_or_default();
+    let (header, lines) = match list_lines(&library, &data, searched) {
+        Ok(list) => list,
+        Err(e) => {
+            error!(
+                "Storage error while paging a list in chat {}: {}",
+                message.chat.id, e
+            );
+            bot.answer_callback_query(query.id.clone())
+                .text(format!("❌ Bir hata oluştu: {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
+    bot.answer_callback_query(query.id.clone()).await?;
+
+    let (text, keyboard) = if lines.is_empty() {
+        (empty_list_text(data.view, data.kind), None)
+    } else {
+        list_page(data, &header, &lines)
+    };
+    let edit = bot.edit_message_text(message.chat.id, message.id, text);
+    let result = match keyboard {
+        Some(keyboard) => edit.reply_markup(keyboard).await,
+        None => edit.await,
+    };
+    match result {
+        // Pressing a button twice before the edit lands redraws the same page.
+        Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// The page `data` points at, with its buttons.
+fn list_page(
+    data: PageData,
+    header: &str,
+    lines: &[String],
+) -> (String, Option<InlineKeyboardMarkup>) {
+    let pages = page_count(lines.len());
+    let (page, shown) = page_of(lines, data.page);
+    let mut text = if pages > 1 {
+        format!("{} (sayfa {}/{})\n", header, page, pages)
+    } else {
+        format!("{}\n", header)
+    };
+    for line in shown {
+        text.push_str(&format!("- {}\n", line));
+    }
+    (text, page_keyboard(PageData { page, ..data }, pages))
+}
+
+/// The header and every line of a list, sorted as asked. Searches are run
+/// again from `searched`, which the callback data has no room for.
+fn list_lines(
+    library: &Library,
+    data: &PageData,
+    searched: &str,
+) -> io::Result<(String, Vec<String>)> {
+    let (ns, kind, order) = (&data.ns, data.kind, data.order);
+    match data.view {
+        View::Search => {
+            let lines = search_lines(library, ns, searched)?;
+            Ok((
+                format!("🔎 '{}' için {} sonuç:", searched, lines.len()),
+                lines,
+            ))
+        }
+        View::Catalogue => {
+            let mut all = library.catalogue(kind);
+            order.sort(&mut all);
+            let lines = all
+                .iter()
+                .map(|title| {
+                    if title.tags.is_empty() {
+                        title.to_string()
+                    } else {
+                        format!("{} 🏷️ {}", title, title.tags.join(", "))
+                    }
+                })
+                .collect();
+            Ok((
+                format!("{} Tüm {}:", kind.emoji(), kind.title_plural()),
+                lines,
+            ))
+        }
+        View::Unwatched => {
+            let mut unwatched = library.unwatched(ns, kind)?;
+            order.sort_by(&mut unwatched, |(t, _)| t);
+            let lines = unwatched
+                .iter()
+                .map(|(title, status)| match status {
+                    Some(status) => format!("{} — {}", title, status),
+                    None => title.to_string(),
+                })
+                .collect();
+            Ok((
+                format!("{} İzlenmemiş {}:", kind.emoji(), kind.title_plural()),
+                lines,
+            ))
+        }
+        View::Watched => {
+            let ratings: HashMap<Title, Rating> = library.ratings(ns, kind)?.into_iter().collect();
+            let counts = library.watch_counts(ns, kind)?;
+            let mut titles: Vec<Title> = library
+                .watched(ns, kind)?
+                .iter()
+                .map(|t| Title::parse(t))
+                .collect();
+            order.sort(&mut titles);
+            let lines = titles
+                .iter()
+                .map(|title| {
+                    let mut line = title.to_string();
+                    if let Some(times) = counts.get(&title.to_string()).filter(|&&n| n > 1) {
+                        line.push_str(&format!(" — 🔁 {} kez", times));
+                    }
+                    if let Some(rating) = ratings.get(title) {
+                        line.push_str(&format!(" — {}", rating));
+                    }
+                    line
+                })
+                .collect();
+            Ok((
+                format!("{} İzlediğin {}:", kind.emoji(), kind.title_plural()),
+                lines,
+            ))
+        }
+    }
+}
+
+fn empty_list_text(view: View, kind: Kind) -> String {
+    match view {
+        View::Search => "🔍 Bu aramaya uyan bir sonuç kalmadı.".to_string(),
+        View::Catalogue => format!(
+            "Henüz katalogda kayıtlı bir {} yok. `/{}_ekle` komutunu kullanarak ekleyebilirsin.",
+            kind.key(),
+            kind.key()
+        ),
+        View::Unwatched => format!(
+            "🎉 Harika! Tüm {}i izlemişsin veya listen boş. Yeni {} eklemek için `/{}_ekle` komutunu kullanabilirsin.",
+            kind.plural(),
+            kind.plural(),
+            kind.key()
+        ),
+        View::Watched => format!(
+            "Henüz izlenmiş bir {} yok. `/{}_oner` komutunu kullanarak ilk {} öner!",
+            kind.key(),
+            kind.key(),
+            kind.yours()
+        ),
+    }
+}
+
+async fn show_stats(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+) -> ResponseResult<()> {
+    match compute_stats(library, ns) {
+        Ok(stats) => {
+            bot.send_message(msg.chat.id, stats.to_string()).await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
+}
+
+async fn send_charts(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+) -> ResponseResult<()> {
+    let charts = match compute_stats(library, ns).and_then(|stats| charts::render_all(&stats)) {
+        Ok(charts) => charts,
+        Err(e) => {
+            error!("Failed to draw charts for {}: {}", ns, e);
+            bot.send_message(msg.chat.id, format!("❌ Grafikler çizilemedi: {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
+    if charts.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Grafik çizecek kadar veri yok. Önce `/film_ekle` ile kataloğa bir şeyler ekle.",
+        )
+        .await?;
+        return Ok(());
+    }
+    for (i, chart) in charts.into_iter().enumerate() {
+        let file = InputFile::memory(chart.png).file_name(format!("grafik_{}.png", i + 1));
+        bot.send_photo(msg.chat.id, file)
+            .caption(chart.caption)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn year_in_review(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+    arg: &str,
+) -> ResponseResult<()> {
+    let mut year = Local::now().year();
+    let mut card = false;
+    for word in arg.split_whitespace() {
+        match word.parse::<i32>() {
+            Ok(y) if (1900..=9999).contains(&y) => year = y,
+            _ if normalize::fold(word) == "kart" => card = true,
+            _ => {
+                bot.send_message(
+                    msg.chat.id,
+                    "Kullanım: `/yil_ozeti [yıl] [kart]`, örn: `/yil_ozeti 2025 kart`",
+                )
+                .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let review = match review_of(library, ns, year) {
+        Ok(Some(review)) => review,
+        Ok(None) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "{} yılında tarihli bir izlemen yok. İzlediklerini `/izlenen_film_ekle` veya `/izlenen_dizi_ekle` ile kaydedersen tarihleri de tutulur.",
+                    year
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    bot.send_message(msg.chat.id, review.to_string()).await?;
+    if card {
+        match charts::render_review_card(&review) {
+            Ok(png) => {
+                let file = InputFile::memory(png).file_name(format!("yil_ozeti_{}.png", year));
+                bot.send_photo(msg.chat.id, file).await?;
+            }
+            Err(e) => {
+                error!("Failed to draw the {} review card for {}: {}", year, ns, e);
+                bot.send_message(msg.chat.id, format!("❌ Kart çizilemedi: {}", e))
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn export(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+    format: &str,
+) -> ResponseResult<()> {
+    let Some(format) = export::Format::parse(format) else {
+        bot.send_message(
+            msg.chat.id,
+            "Kullanım: `/disa_aktar csv`, `/disa_aktar json` veya `/disa_aktar md`",
+        )
+        .await?;
+        return Ok(());
+    };
+    let records = match export::records(library, ns) {
+        Ok(records) => records,
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    if records.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Dışa aktaracak bir şey yok. Önce `/film_ekle` ile kataloğa bir şeyler ekle.",
+        )
+        .await?;
+        return Ok(());
+    }
+    let contents = match format.write(&records) {
+        Ok(contents) => contents,
+        Err(e) => {
+            error!("Failed to export {} as {:?}: {}", ns, format, e);
+            bot.send_message(msg.chat.id, format!("❌ Dosya hazırlanamadı: {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
+    let file = InputFile::memory(contents).file_name(format!(
+        "arsiv_{}.{}",
+        Local::now().format("%Y-%m-%d"),
+        format.extension()
+    ));
+    bot.send_document(msg.chat.id, file)
+        .caption(format!("📦 {} kayıt dışa aktarıldı.", records.len()))
+        .await?;
+    Ok(())
+}
+
+async fn import_document(
+    bot: Bot,
+    msg: Message,
+    document: Document,
+    library: Library,
+) -> ResponseResult<()> {
+    let name = document.file_name.clone().unwrap_or_default();
+    info!("Importing '{}' sent to chat {}", name, msg.chat.id);
+    if document.file.size > import::MAX_IMPORT_BYTES {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "❌ '{}' çok büyük, en fazla {} KB'lık dosyaları okuyabilirim.",
+                name,
+                import::MAX_IMPORT_BYTES / 1024
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let file = bot.get_file(document.file.id.clone()).await?;
+    let mut contents = Vec::new();
+    if let Err(e) = bot.download_file(&file.path, &mut contents).await {
+        error!("Failed to download '{}': {}", name, e);
+        bot.send_message(msg.chat.id, format!("❌ '{}' indirilemedi: {}", name, e))
+            .await?;
+        return Ok(());
+    }
+
+    let target = import::Target::parse(msg.caption().unwrap_or(""));
+    let (items, invalid) = match import::parse(&name, &contents, target) {
+        Ok(parsed) => parsed,
+        Err(problem) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("❌ '{}' okunamadı: {}.\n\n{}", name, problem, IMPORT_HELP),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    let actor = Actor::from_message(&msg);
+    let applied = blocking(&library, move |library| {
+        import::apply(library, &actor, items)
+    });
+    let mut summary = match applied.await {
+        Ok(summary) => summary,
+        Err(e) => return report_storage_error(&bot, &msg, e).await,
+    };
+    summary.invalid.extend(invalid);
+    summary.invalid.sort();
+
+    let mut reply = format!(
+        "📥 '{}' içe aktarıldı.\n✅ Eklenen: {}\nℹ️ Zaten olan: {}\n⚠️ Geçersiz: {}",
+        name,
+        summary.added.len(),
+        summary.skipped.len(),
+        summary.invalid.len()
+    );
+    for (line, problem) in summary.invalid.iter().take(MAX_INVALID_SHOWN) {
+        reply.push_str(&format!("\nSatır {}: {}", line, problem));
+    }
+    if summary.invalid.len() > MAX_INVALID_SHOWN {
+        reply.push_str(&format!(
+            "\n… ve {} satır daha.",
+            summary.invalid.len() - MAX_INVALID_SHOWN
+        ));
+    }
+    bot.send_message(msg.chat.id, reply).await?;
+    Ok(())
+}
+
+fn review_of(library: &Library, ns: &Namespace, year: i32) -> io::Result<Option<YearReview>> {
+    let mut ratings = HashMap::new();
+    let mut catalogues = HashMap::new();
+    for kind in Kind::ALL {
+        ratings.insert(kind, library.ratings(ns, kind)?);
+        catalogues.insert(kind, library.catalogue(kind));
+    }
+    Ok(YearReview::compute(
+        year,
+        &library.history(ns)?,
+        &ratings,
+        &catalogues,
+    ))
+}
+
+fn compute_stats(library: &Library, ns: &Namespace) -> io::Result<Stats> {
+    let (films, series) = (
+        library.catalogue(Kind::Film),
+        library.catalogue(Kind::Series),
+    );
+    let watched_films = library.watched(ns, Kind::Film)?;
+    let watched_series = library.watched(ns, Kind::Series)?;
+    let progress = library
+        .progress(ns)?
+        .into_iter()
+        .map(|(title, episode)| (title.to_string(), episode))
         .collect();
+    Ok(Stats::compute(
+        stats::Catalogue {
+            titles: &films,
+            watched: &watched_films,
+        },
+        stats::Catalogue {
+            titles: &series,
+            watched: &watched_series,
+        },
+        &progress,
+        &library.history(ns)?,
+        Utc::now(),
+    ))
+}
 
-    let mut rng = rand::thread_rng();
-    unwatched.choose(&mut rng).cloned()
+async fn set_status(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    kind: Kind,
+    arg: &str,
+) -> ResponseResult<()> {
+    let ns = &actor.ns;
+    let parsed =
+        split_status_arg(arg).and_then(|(name, word)| Some((name, Status::parse_arg(word)?)));
+    let Some((name, status)) = parsed else {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Kullanım: `/{}_durum <{} Adı> <durum>`\nDurumlar: planli, izliyorum, beklemede, birakildi, tamamlandi, yok",
+                kind.key(),
+                kind.title()
+            ),
+        )
+        .await?;
+        return Ok(());
+    };
+    let Some(title) = resolve_title(bot, msg, library, kind, name).await? else {
+        return Ok(());
+    };
+
+    let (by, changed) = (*actor, title.clone());
+    let change = blocking(library, move |library| {
+        library.set_status(&by, kind, &changed, status)
+    });
+    match change.await {
+        Ok(()) => {
+            info!("Status of '{}' set to {:?} for {}.", title, status, ns);
+            let reply = match status {
+                Some(status) => format!("✅ '{}' durumu: {}", title, status),
+                None => format!("✅ '{}' için kayıtlı durum silindi.", title),
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
 }
 
-fn mark_series_as_watched(series: &str) {
-    let mut watched_series = load_watched_series("izlenen_diziler.txt");
-    if watched_series.insert(series.to_string()) {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open("izlenen_diziler.txt")
-            .expect("❌ izlenen_diziler.txt dosyasına yazılamıyor!");
+async fn list_statuses(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+) -> ResponseResult<()> {
+    let mut response_text = String::new();
+    for kind in Kind::ALL {
+        let statuses = match library.statuses(ns, kind) {
+            Ok(statuses) => statuses,
+            Err(e) => return report_storage_error(bot, msg, e).await,
+        };
+        let completed = statuses
+            .iter()
+            .filter(|(_, s)| *s == Status::Completed)
+            .count();
+        if statuses.is_empty() {
+            continue;
+        }
 
-        let mut sorted_series: Vec<&String> = watched_series.iter().collect();
-        sorted_series.sort();
-        for s in sorted_series {
-            writeln!(file, "{}", s).expect("❌ Diziye yazma başarısız!");
+        response_text.push_str(&format!("{} {}\n", kind.emoji(), kind.title_plural()));
+        for status in Status::ALL {
+            if status == Status::Completed {
+                continue;
+            }
+            let titles: Vec<String> = statuses
+                .iter()
+                .filter(|(_, s)| *s == status)
+                .map(|(t, _)| t.to_string())
+                .collect();
+            if !titles.is_empty() {
+                response_text.push_str(&format!("{}:\n", status));
+                for title in titles {
+                    response_text.push_str(&format!("- {}\n", title));
+                }
+            }
         }
-        info!("Successfully marked series '{}' as watched.", series);
+        response_text.push_str(&format!(
+            "{}: {} {}\n\n",
+            Status::Completed,
+            completed,
+            kind.key()
+        ));
+    }
+
+    if response_text.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Henüz durum verdiğin bir şey yok. `/film_durum <Film Adı> planli` ile başlayabilirsin.",
+        )
+        .await?;
     } else {
-        info!("Dizi '{}' zaten izlenenler listesinde.", series);
+        send_long_message(bot.clone(), msg.chat.id, response_text).await?;
+    }
+    Ok(())
+}
+
+async fn list_history(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+    page: &str,
+) -> ResponseResult<()> {
+    let page = match page.trim() {
+        "" => 1,
+        page => match page.parse::<usize>() {
+            Ok(page) if page > 0 => page,
+            _ => {
+                bot.send_message(msg.chat.id, "Kullanım: `/gecmis [sayfa]`, örn: `/gecmis 2`")
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+    let history = match library.history(ns) {
+        Ok(history) => history,
+        Err(e) => return report_storage_error(bot, msg, e).await,
+    };
+    if history.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Henüz kayıtlı bir izleme geçmişin yok. `/izlenen_film_ekle <Film Adı>` ile başlayabilirsin.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Number each watch of a title in order, so rewatches show up as such.
+    let mut seen: HashMap<(Kind, &str), usize> = HashMap::new();
+    let mut lines: Vec<String> = history
+        .iter()
+        .map(|event| {
+            let nth = seen.entry((event.kind, &event.title)).or_insert(0);
+            *nth += 1;
+            let rewatch = if *nth > 1 {
+                format!(" (🔁 {}. kez)", nth)
+            } else {
+                String::new()
+            };
+            format!(
+                "{} {} {}{}",
+                event.local_date(),
+                event.kind.emoji(),
+                event.title,
+                rewatch
+            )
+        })
+        .collect();
+    lines.reverse();
+
+    let pages = lines.len().div_ceil(HISTORY_PAGE_SIZE);
+    if page > pages {
+        bot.send_message(
+            msg.chat.id,
+            format!("Hata: geçmişin {} sayfadan oluşuyor.", pages),
+        )
+        .await?;
+        return Ok(());
+    }
+    let mut response_text = format!("🕰️ İzleme Geçmişin (sayfa {}/{}):\n", page, pages);
+    for line in lines
+        .iter()
+        .skip((page - 1) * HISTORY_PAGE_SIZE)
+        .take(HISTORY_PAGE_SIZE)
+    {
+        response_text.push_str(&format!("- {}\n", line));
+    }
+    if page < pages {
+        response_text.push_str(&format!("\nDaha eskiler için: /gecmis {}", page + 1));
+    }
+    send_long_message(bot.clone(), msg.chat.id, response_text).await?;
+    Ok(())
+}
+
+async fn rate(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    actor: &Actor,
+    arg: &str,
+) -> ResponseResult<()> {
+    let ns = &actor.ns;
+    let input = split_rating_arg(arg);
+    let rating = match input.rating() {
+        Ok(Some(rating)) => rating,
+        Ok(None) => {
+            bot.send_message(
+                msg.chat.id,
+                "Kullanım: `/puanla [film|dizi] <Ad> | <puan> | [not]`, örn: `/puanla Matilda (1996) | 8 | Çok güzeldi`",
+            )
+            .await?;
+            return Ok(());
+        }
+        Err(problem) => {
+            bot.send_message(msg.chat.id, format!("Hata: {}", problem))
+                .await?;
+            return Ok(());
+        }
+    };
+    let Some((kind, title)) = resolve_any_title(bot, msg, library, input.name).await? else {
+        return Ok(());
+    };
+
+    let (by, rated) = (*actor, title.clone());
+    let change = blocking(library, move |library| {
+        library.rate(&by, kind, &rated, rating)
+    });
+    match change.await {
+        Ok(Some(stored)) => {
+            info!("'{}' rated {}/10 by {}.", title, stored.score, ns);
+            bot.send_message(msg.chat.id, format!("✅ '{}': {}", title, stored))
+                .await?;
+        }
+        Ok(None) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Hata: '{}' henüz izlediklerin arasında değil. Önce `/izlenen_{}_ekle {}` ile izlenenlere ekle.",
+                    title,
+                    kind.key(),
+                    title
+                ),
+            )
+            .await?;
+        }
+        Err(e) => report_storage_error(bot, msg, e).await?,
+    }
+    Ok(())
+}
+
+/// Like [`resolve_title`], but for commands that work on both films and
+/// series. A leading `film` or `dizi` picks the catalogue; otherwise both are
+/// searched and a name found in both has to be qualified.
+async fn resolve_any_title(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    name_raw: &str,
+) -> ResponseResult<Option<(Kind, Title)>> {
+    let name_raw = name_raw.trim();
+    if let Some((first, rest)) = name_raw.split_once(char::is_whitespace) {
+        if let Some(kind) = Kind::ALL
+            .into_iter()
+            .find(|k| k.key() == first.to_lowercase())
+        {
+            let title = resolve_title(bot, msg, library, kind, rest).await?;
+            return Ok(title.map(|t| (kind, t)));
+        }
+    }
+
+    let mut found: Vec<(Kind, Title)> = Vec::new();
+    let mut fallback = Kind::Film;
+    for kind in Kind::ALL {
+        match library.lookup(kind, name_raw) {
+            Lookup::Found(title) => found.push((kind, title)),
+            Lookup::Ambiguous(..) if found.is_empty() => fallback = kind,
+            _ => {}
+        }
+    }
+    match found.len() {
+        1 => Ok(found.pop()),
+        0 => {
+            // Let the catalogue with near matches explain what went wrong.
+            let title = resolve_title(bot, msg, library, fallback, name_raw).await?;
+            Ok(title.map(|t| (fallback, t)))
+        }
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "'{}' hem film hem dizi olarak kayıtlı. Başına `film` veya `dizi` yazarak belirt.",
+                    name_raw
+                ),
+            )
+            .await?;
+            Ok(None)
+        }
     }
 }
 
-fn add_series_to_file(series: &str) -> io::Result<bool> {
-    let mut all_series = load_series("diziler.txt");
-    if all_series.insert(series.to_string()) {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open("diziler.txt")?;
+async fn list_best(
+    bot: &Bot,
+    msg: &Message,
+    library: &Library,
+    ns: &Namespace,
+) -> ResponseResult<()> {
+    let mut response_text = String::new();
+    for kind in Kind::ALL {
+        let ratings = match library.ratings(ns, kind) {
+            Ok(ratings) => ratings,
+            Err(e) => return report_storage_error(bot, msg, e).await,
+        };
+        if ratings.is_empty() {
+            continue;
+        }
+        response_text.push_str(&format!(
+            "{} En İyi {}:\n",
+            kind.emoji(),
+            kind.title_plural()
+        ));
+        for (i, (title, rating)) in ratings.iter().enumerate() {
+            response_text.push_str(&format!("{}. {} — {}\n", i + 1, title, rating));
+        }
+        response_text.push('\n');
+    }
+
+    if response_text.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Henüz puan verdiğin bir şey yok. `/puanla <Ad> | <puan>` ile başlayabilirsin.",
+        )
+        .await?;
+    } else {
+        send_long_message(bot.clone(), msg.chat.id, response_text).await?;
+    }
+    Ok(())
+}
 
-        let mut sorted_series: Vec<&String> = all_series.iter().collect();
-        sorted_series.sort();
-        for s in sorted_series {
-            writeln!(file, "{}", s)?;
+async fn list_problems(bot: &Bot, msg: &Message, library: &Library) -> ResponseResult<()> {
+    let mut response_text = String::new();
+    for kind in Kind::ALL {
+        let problems = library.problems(kind);
+        if problems.is_empty() {
+            continue;
         }
-        info!("Dizi '{}' diziler.txt dosyasına eklendi.", series);
-        Ok(true)
+        response_text.push_str(&format!("⚠️ {}:\n", kind.title_plural()));
+        for (title, problem) in problems {
+            response_text.push_str(&format!("- {}: {}\n", title, problem));
+        }
+    }
+
+    if response_text.is_empty() {
+        bot.send_message(msg.chat.id, "✅ Katalogdaki tüm satırlar düzgün görünüyor.")
+            .await?;
     } else {
-        info!("Dizi '{}' zaten diziler.txt dosyasında mevcut.", series);
-        Ok(false)
+        send_long_message(bot.clone(), msg.chat.id, response_text).await?;
+    }
+    Ok(())
+}
+
+/// Replies with the accepted values and returns `None` for an unknown order.
+async fn parse_order(bot: &Bot, msg: &Message, arg: &str) -> ResponseResult<Option<ListOrder>> {
+    let order = ListOrder::parse(arg);
+    if order.is_none() {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Hata: '{}' bilinen bir sıralama değil. `ad`, `yil` veya `sezon` kullanabilirsin.",
+                arg.trim()
+            ),
+        )
+        .await?;
     }
+    Ok(order)
+}
+
+async fn send_long_message(bot: Bot, chat_id: ChatId, text: String) -> ResponseResult<()> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut current_chunk = String::new();
+
+    for line in lines {
+        if current_chunk.len() + line.len() + 1 > MAX_MESSAGE_LENGTH && !current_chunk.is_empty() {
+            bot.send_message(chat_id, current_chunk.clone()).await?;
+            current_chunk.clear();
+        }
+        current_chunk.push_str(line);
+        current_chunk.push('\n');
+    }
+
+    if !current_chunk.is_empty() {
+        bot.send_message(chat_id, current_chunk).await?;
+    }
+    Ok(())
+}
+
+/// Runs a change to the library on a blocking thread. Writes touch the disk
+/// under the library's lock, and doing that on the async runtime would hold
+/// up every other chat's updates while a slow disk or a large import works.
+async fn blocking<T, F>(library: &Library, change: F) -> io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Library) -> io::Result<T> + Send + 'static,
+{
+    let library = library.clone();
+    tokio::task::spawn_blocking(move || change(&library))
+        .await
+        .map_err(io::Error::other)?
+}
+
+async fn report_storage_error(bot: &Bot, msg: &Message, e: io::Error) -> ResponseResult<()> {
+    error!("Storage error while handling chat {}: {}", msg.chat.id, e);
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "❌ Liste okunurken veya kaydedilirken bir hata oluştu: {}",
+            e
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Picks up to `count` unwatched titles matching the wanted filter, never a
+/// dropped one. With `prefer_in_progress` titles being watched or on hold
+/// are picked first when there are any. Picks are weighted and avoid the
+/// chat's recent suggestions, see [`Recommender`].
+fn get_random_unwatched(
+    library: &Library,
+    recommender: &Recommender,
+    chat_id: ChatId,
+    ns: &Namespace,
+    kind: Kind,
+    wanted: &Wanted,
+    count: usize,
+) -> io::Result<Vec<Title>> {
+    let (filter, prefer_in_progress) = (&wanted.filter, wanted.prefer_in_progress);
+    let added_dates = library.added_dates(kind)?;
+    let mut candidates: Vec<Candidate> = library
+        .unwatched(ns, kind)?
+        .into_iter()
+        .filter(|(title, status)| *status != Some(Status::Dropped) && filter.matches(title))
+        .map(|(title, status)| Candidate {
+            added_at: added_dates.get(&title.to_string()).copied(),
+            title,
+            status,
+        })
+        .collect();
+    if prefer_in_progress
+        && candidates
+            .iter()
+            .any(|c| c.status.is_some_and(Status::is_in_progress))
+    {
+        candidates.retain(|c| c.status.is_some_and(Status::is_in_progress));
+    }
+
+    let taste = Taste::from_ratings(&library.catalogue(kind), &library.ratings(ns, kind)?);
+    Ok(recommender.pick(chat_id.0, kind, &candidates, &taste, Utc::now(), count))
 }
@@ -0,0 +1,127 @@
+use crate::normalize::fold;
+use crate::title::Title;
+
+/// Scores below this are not offered at all.
+const MIN_SCORE: f64 = 0.6;
+
+/// A catalogue entry and how closely it matches the input, from 0 to 1.
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub title: Title,
+    pub score: f64,
+}
+
+/// Ranks `titles` by similarity to `input`, best first, and keeps the top
+/// `limit` that are close enough to be a typo of each other: `iron men`
+/// finds `Iron Man (2008)` and `misafr` finds `Misafir (2019)`.
+pub fn rank(input: &str, titles: &[Title], limit: usize) -> Vec<Match> {
+    let input = fold(input);
+    if input.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<Match> = titles
+        .iter()
+        .map(|title| Match {
+            title: title.clone(),
+            score: score(&input, title),
+        })
+        .filter(|m| m.score >= MIN_SCORE)
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.title.name.cmp(&b.title.name))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// The better of whole-string and word-by-word similarity, against either
+/// the bare name or the full line.
+pub fn score(input_folded: &str, title: &Title) -> f64 {
+    let name = fold(&title.name);
+    let line = fold(&title.to_string());
+    [
+        similarity(input_folded, &name),
+        similarity(input_folded, &line),
+        token_similarity(input_folded, &name),
+    ]
+    .into_iter()
+    .fold(0.0, f64::max)
+}
+
+/// 1 minus the edit distance relative to the longer string.
+fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+/// How well each typed word matches some word of the title, on average.
+fn token_similarity(input: &str, name: &str) -> f64 {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let typed: Vec<&str> = input.split_whitespace().collect();
+    if words.is_empty() || typed.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = typed
+        .iter()
+        .map(|t| words.iter().map(|w| similarity(t, w)).fold(0.0, f64::max))
+        .sum();
+    // Typing fewer words than the title has is a weaker match.
+    total / typed.len().max(words.len()) as f64
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(lines: &[&str]) -> Vec<Title> {
+        lines.iter().map(|l| Title::parse(l)).collect()
+    }
+
+    #[test]
+    fn finds_titles_despite_typos() {
+        let catalogue = titles(&[
+            "Iron Man (2008)",
+            "Iron Man 2 (2010)",
+            "Misafir (2019)",
+            "Matilda (1996)",
+        ]);
+
+        let found = rank("Iron Men", &catalogue, 5);
+        assert_eq!(found[0].title.to_string(), "Iron Man (2008)");
+        assert!(found[0].score > found[1].score);
+
+        let found = rank("misafr", &catalogue, 5);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].title.name, "Misafir");
+
+        assert!(rank("zzz", &catalogue, 5).is_empty());
+        assert_eq!(rank("iron man", &catalogue, 1).len(), 1);
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("günah", "gunah"), 1);
+    }
+}
@@ -0,0 +1,127 @@
+use once_cell::sync::Lazy;
+use std::env;
+use std::fmt;
+use teloxide::types::{CallbackQuery, Chat, Message, User};
+
+/// When set, everyone in a group chat shares the chat's list instead of
+/// keeping their own.
+static SHARED_GROUP_LISTS: Lazy<bool> = Lazy::new(|| {
+    env::var("ORTAK_GRUP_LISTESI")
+        .map(|v| matches!(v.trim(), "1" | "true" | "evet"))
+        .unwrap_or(false)
+});
+
+/// Whose watched history a command reads and writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    User(u64),
+    Chat(i64),
+}
+
+impl Namespace {
+    pub fn from_message(msg: &Message) -> Self {
+        Namespace::resolve(msg.from.as_ref(), &msg.chat)
+    }
+
+    /// The namespace of whoever pressed the button, which in a group is not
+    /// necessarily whoever the message was sent to.
+    pub fn from_callback(query: &CallbackQuery) -> Option<Self> {
+        let chat = query.message.as_ref()?.chat();
+        Some(Namespace::resolve(Some(&query.from), chat))
+    }
+
+    fn resolve(user: Option<&User>, chat: &Chat) -> Self {
+        Namespace::resolve_with(user, chat, *SHARED_GROUP_LISTS)
+    }
+
+    fn resolve_with(user: Option<&User>, chat: &Chat, shared_groups: bool) -> Self {
+        let shared = shared_groups && !chat.is_private();
+        match user {
+            Some(user) if !shared => Namespace::User(user.id.0),
+            _ => Namespace::Chat(chat.id.0),
+        }
+    }
+
+    pub fn key(&self) -> String {
+        match self {
+            Namespace::User(id) => format!("kullanici_{}", id),
+            Namespace::Chat(id) => format!("sohbet_{}", id),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        if let Some(id) = key.strip_prefix("kullanici_") {
+            return id.parse().ok().map(Namespace::User);
+        }
+        key.strip_prefix("sohbet_")?
+            .parse()
+            .ok()
+            .map(Namespace::Chat)
+    }
+
+    /// A key, or a bare Telegram id as people copy it from elsewhere: group
+    /// ids are negative, user ids positive.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some(ns) = Namespace::from_key(value) {
+            return Some(ns);
+        }
+        let id: i64 = value.parse().ok()?;
+        Some(match u64::try_from(id) {
+            Ok(id) => Namespace::User(id),
+            Err(_) => Namespace::Chat(id),
+        })
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn namespaces_follow_the_user_unless_groups_share() {
+        let user: User =
+            serde_json::from_value(json!({"id": 7, "is_bot": false, "first_name": "Ada"})).unwrap();
+        let private: Chat =
+            serde_json::from_value(json!({"id": 7, "type": "private", "first_name": "Ada"}))
+                .unwrap();
+        let group: Chat =
+            serde_json::from_value(json!({"id": -42, "type": "group", "title": "Film Gecesi"}))
+                .unwrap();
+
+        for shared in [false, true] {
+            assert_eq!(
+                Namespace::resolve_with(Some(&user), &private, shared),
+                Namespace::User(7)
+            );
+            assert_eq!(
+                Namespace::resolve_with(None, &group, shared),
+                Namespace::Chat(-42)
+            );
+        }
+        assert_eq!(
+            Namespace::resolve_with(Some(&user), &group, false),
+            Namespace::User(7)
+        );
+        assert_eq!(
+            Namespace::resolve_with(Some(&user), &group, true),
+            Namespace::Chat(-42)
+        );
+
+        for ns in [Namespace::User(7), Namespace::Chat(-42)] {
+            assert_eq!(Namespace::from_key(&ns.key()), Some(ns));
+            assert_eq!(Namespace::parse(&ns.key()), Some(ns));
+        }
+        assert_eq!(Namespace::parse(" 7 "), Some(Namespace::User(7)));
+        assert_eq!(Namespace::parse("-42"), Some(Namespace::Chat(-42)));
+        assert_eq!(Namespace::from_key("kullanici_-1"), None);
+        assert_eq!(Namespace::parse("ada"), None);
+    }
+}
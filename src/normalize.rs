@@ -0,0 +1,78 @@
+use std::borrow::Cow;
+
+/// Folds text for comparison: Turkish-aware lowercasing, letters with
+/// diacritics mapped to their ASCII base and whitespace collapsed, so
+/// `ZAMANIN KAPILARI`, `zamanin kapilari` and `Zamanın Kapıları` all fold to
+/// the same string.
+///
+/// `to_lowercase` alone turns `I` into `i` instead of `ı` and `İ` into `i`
+/// plus a combining dot; folding both dotted and dotless forms to `i` sidesteps
+/// the question of which keyboard the user typed on.
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for word in text.split_whitespace() {
+        if !folded.is_empty() {
+            folded.push(' ');
+        }
+        for c in word.chars() {
+            match c {
+                'I' | 'İ' | 'ı' | 'î' | 'Î' => folded.push('i'),
+                'Ş' | 'ş' => folded.push('s'),
+                'Ğ' | 'ğ' => folded.push('g'),
+                'Ü' | 'ü' | 'û' | 'Û' => folded.push('u'),
+                'Ö' | 'ö' => folded.push('o'),
+                'Ç' | 'ç' => folded.push('c'),
+                'Â' | 'â' => folded.push('a'),
+                // Left behind by decomposed input such as `i̇`.
+                '\u{0307}' => {}
+                c => folded.extend(c.to_lowercase()),
+            }
+        }
+    }
+    folded
+}
+
+/// Replaces control characters such as tabs and line breaks with spaces.
+/// Titles, tags and notes are stored one per line, and the text backend
+/// keeps them in tab separated rows, so neither may appear inside them.
+pub fn single_line(text: &str) -> Cow<'_, str> {
+    if text.contains(char::is_control) {
+        Cow::Owned(
+            text.chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_turkish_letters_and_case() {
+        let expected = "zamanin kapilari";
+        for input in [
+            "Zamanın Kapıları",
+            "ZAMANIN KAPILARI",
+            "zamanin  kapilari",
+            "ZAMANİN KAPİLARİ",
+        ] {
+            assert_eq!(fold(input), expected, "{}", input);
+        }
+        assert_eq!(
+            fold("Şeytanın Gölgesi Çağrı Üzüm"),
+            "seytanin golgesi cagri uzum"
+        );
+        assert_eq!(fold("i\u{0307}stanbul"), "istanbul");
+        assert_eq!(fold("13 Günah (2014)"), "13 gunah (2014)");
+    }
+
+    #[test]
+    fn control_characters_become_spaces() {
+        assert_eq!(single_line("Matilda (1996)"), "Matilda (1996)");
+        assert_eq!(single_line("a\tb\r\nc\u{0}"), "a b  c ");
+    }
+}
@@ -0,0 +1,150 @@
+use crate::namespace::Namespace;
+use crate::storage::Kind;
+use crate::title::ListOrder;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+/// Callback data prefix of the page buttons under a list.
+const PREFIX: &str = "sayfa";
+pub const PAGE_SIZE: usize = 20;
+
+/// Which list a paginated message shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    Catalogue,
+    Unwatched,
+    Watched,
+    /// `/ara` results of both kinds, closest first, so `kind` and `order`
+    /// are not used. The query is read back from the message.
+    Search,
+}
+
+impl View {
+    const ALL: [View; 4] = [
+        View::Catalogue,
+        View::Unwatched,
+        View::Watched,
+        View::Search,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            View::Catalogue => "tum",
+            View::Unwatched => "izlenmemis",
+            View::Watched => "izlenen",
+            View::Search => "ara",
+        }
+    }
+}
+
+/// Everything needed to draw a page again, so the buttons work without any
+/// state kept on our side and keep working after a restart. The namespace is
+/// whoever asked for the list. Callback data comes back from the client and
+/// can be made up, so a press is only served when it matches the presser's
+/// own namespace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageData {
+    pub view: View,
+    pub kind: Kind,
+    pub order: ListOrder,
+    pub ns: Namespace,
+    /// Starts at 1.
+    pub page: usize,
+}
+
+impl PageData {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            PREFIX,
+            self.view.key(),
+            self.kind.key(),
+            self.order.key(),
+            self.page,
+            self.ns.key()
+        )
+    }
+
+    pub fn parse(data: &str) -> Option<Self> {
+        let mut parts = data.splitn(6, ':');
+        if parts.next()? != PREFIX {
+            return None;
+        }
+        let view = parts.next()?;
+        Some(PageData {
+            view: View::ALL.into_iter().find(|v| v.key() == view)?,
+            kind: Kind::from_key(parts.next()?)?,
+            order: ListOrder::parse(parts.next()?)?,
+            page: parts.next()?.parse().ok().filter(|&p| p > 0)?,
+            ns: Namespace::from_key(parts.next()?)?,
+        })
+    }
+
+    fn with_page(self, page: usize) -> Self {
+        PageData { page, ..self }
+    }
+}
+
+/// Number of pages `len` lines take up; an empty list is still one page.
+pub fn page_count(len: usize) -> usize {
+    len.div_ceil(PAGE_SIZE).max(1)
+}
+
+/// The lines of a 1-based `page`, which is clamped to the last page when the
+/// list shrank since the buttons were sent.
+pub fn page_of(lines: &[String], page: usize) -> (usize, &[String]) {
+    let page = page.clamp(1, page_count(lines.len()));
+    let start = (page - 1) * PAGE_SIZE;
+    let end = (start + PAGE_SIZE).min(lines.len());
+    (page, &lines[start..end])
+}
+
+/// Previous and next buttons as they apply; `None` for a single page.
+pub fn page_keyboard(data: PageData, pages: usize) -> Option<InlineKeyboardMarkup> {
+    let mut row = Vec::new();
+    if data.page > 1 {
+        row.push(InlineKeyboardButton::callback(
+            "◀️ Önceki",
+            data.with_page(data.page - 1).encode(),
+        ));
+    }
+    if data.page < pages {
+        row.push(InlineKeyboardButton::callback(
+            "Sonraki ▶️",
+            data.with_page(data.page + 1).encode(),
+        ));
+    }
+    (!row.is_empty()).then(|| InlineKeyboardMarkup::new(vec![row]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_data_round_trips_and_pages_are_clamped() {
+        let data = PageData {
+            view: View::Unwatched,
+            kind: Kind::Series,
+            order: ListOrder::Seasons,
+            ns: Namespace::Chat(-1_001_234_567_890),
+            page: 12,
+        };
+        let encoded = data.encode();
+        assert!(encoded.len() <= 64);
+        assert_eq!(PageData::parse(&encoded), Some(data));
+        let search = PageData {
+            view: View::Search,
+            ..data
+        };
+        assert_eq!(PageData::parse(&search.encode()), Some(search));
+        assert_eq!(PageData::parse("sayfa:tum:film:ad:0:kullanici_1"), None);
+        assert_eq!(PageData::parse("oner:izledim:film:00"), None);
+
+        let lines: Vec<String> = (0..45).map(|i| i.to_string()).collect();
+        assert_eq!(page_count(lines.len()), 3);
+        let (page, shown) = page_of(&lines, 7);
+        assert_eq!(page, 3);
+        assert_eq!(shown.len(), 5);
+        assert!(page_keyboard(data.with_page(1), 1).is_none());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::namespace::Namespace;
+use crate::rating::Rating;
+use crate::storage::Kind;
+use crate::title::Title;
+use teloxide::dispatching::dialogue::{Dialogue, InMemStorage};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+
+/// Callback data prefix of the choice buttons, to tell them apart from
+/// suggestion buttons.
+const PREFIX: &str = "sec";
+/// More candidates than this do not fit as buttons, so only the closest are
+/// offered; typing more of the title narrows them down.
+pub const MAX_CHOICES: usize = 8;
+
+/// The questions the bot asked in a chat and is waiting on a button for.
+/// Dialogues are kept per chat, so in a group several people can have a
+/// question open at once: each person has at most one, and asking again only
+/// replaces their own.
+#[derive(Clone, Debug, Default)]
+pub struct PickState {
+    pending: Vec<Pending>,
+}
+
+/// `/izlenen_*_ekle` matched several titles; the buttons pick one.
+#[derive(Clone, Debug)]
+pub struct Pending {
+    /// Whoever asked; `None` for messages sent on behalf of a channel.
+    pub user: Option<u64>,
+    pub ns: Namespace,
+    pub kind: Kind,
+    pub candidates: Vec<Title>,
+    pub rating: Option<Rating>,
+    /// The message with the buttons, which tells questions apart.
+    pub message_id: MessageId,
+}
+
+impl PickState {
+    pub fn ask(&mut self, question: Pending) {
+        self.pending.retain(|p| p.user != question.user);
+        self.pending.push(question);
+    }
+
+    /// The question whose buttons are under `message_id`, if still open.
+    pub fn find(&self, message_id: MessageId) -> Option<&Pending> {
+        self.pending.iter().find(|p| p.message_id == message_id)
+    }
+
+    pub fn close(&mut self, message_id: MessageId) {
+        self.pending.retain(|p| p.message_id != message_id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+pub type PickDialogue = Dialogue<PickState, InMemStorage<PickState>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Choice {
+    Index(usize),
+    Cancel,
+}
+
+impl Choice {
+    fn encode(self) -> String {
+        match self {
+            Choice::Index(i) => format!("{}:{}", PREFIX, i),
+            Choice::Cancel => format!("{}:iptal", PREFIX),
+        }
+    }
+
+    pub fn parse(data: &str) -> Option<Self> {
+        match data.strip_prefix(PREFIX)?.strip_prefix(':')? {
+            "iptal" => Some(Choice::Cancel),
+            index => index.parse().ok().map(Choice::Index),
+        }
+    }
+}
+
+/// One button per candidate plus a cancel button.
+pub fn choice_keyboard(candidates: &[Title]) -> InlineKeyboardMarkup {
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, title)| {
+            vec![InlineKeyboardButton::callback(
+                title.to_string(),
+                Choice::Index(i).encode(),
+            )]
+        })
+        .collect();
+    rows.push(vec![InlineKeyboardButton::callback(
+        "❌ Vazgeç",
+        Choice::Cancel.encode(),
+    )]);
+    InlineKeyboardMarkup::new(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_person_keeps_one_open_question() {
+        let question = |user, message| Pending {
+            user: Some(user),
+            ns: Namespace::Chat(-1),
+            kind: Kind::Film,
+            candidates: Vec::new(),
+            rating: None,
+            message_id: MessageId(message),
+        };
+        let mut state = PickState::default();
+        state.ask(question(1, 10));
+        state.ask(question(2, 11));
+        assert_eq!(state.find(MessageId(10)).unwrap().user, Some(1));
+
+        state.ask(question(1, 12));
+        assert!(state.find(MessageId(10)).is_none());
+        assert_eq!(state.find(MessageId(11)).unwrap().user, Some(2));
+        state.close(MessageId(11));
+        state.close(MessageId(12));
+        assert!(state.is_empty());
+    }
+
+    #[test]
+    fn choices_round_trip() {
+        for choice in [Choice::Index(0), Choice::Index(7), Choice::Cancel] {
+            assert_eq!(Choice::parse(&choice.encode()), Some(choice));
+        }
+        assert_eq!(Choice::parse("oner:izledim:film:00"), None);
+        assert_eq!(Choice::parse("sec:x"), None);
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+
+/// A single episode, written as `S02E05`. Episode 0 means "nothing watched
+/// yet in this season", which is what finishing the previous season leaves
+/// behind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Episode {
+    pub season: u32,
+    pub episode: u32,
+}
+
+impl Episode {
+    /// Accepts `S02E05`, `s2e5` and `2x05`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        let (season, episode) = match s.strip_prefix('s') {
+            Some(rest) => rest.split_once('e')?,
+            None => s.split_once('x')?,
+        };
+        let episode = Episode {
+            season: season.parse().ok()?,
+            episode: episode.parse().ok()?,
+        };
+        (episode.season > 0).then_some(episode)
+    }
+
+    pub fn next(self) -> Self {
+        Episode {
+            season: self.season,
+            episode: self.episode + 1,
+        }
+    }
+
+    /// What to watch after this episode, for a series with `seasons` seasons.
+    /// Episode counts are not known, so unless a season was just started the
+    /// season may be over and `/sezon_bitti` is mentioned too.
+    pub fn up_next(self, seasons: Option<u32>) -> String {
+        let next = self.next();
+        if self.episode == 0 {
+            return next.to_string();
+        }
+        if seasons.is_some_and(|last| self.season >= last) {
+            return format!("{} (son sezon; bittiyse `/sezon_bitti`)", next);
+        }
+        let first = Episode {
+            season: self.season + 1,
+            episode: 1,
+        };
+        format!("{}, sezon bittiyse `/sezon_bitti` ile {}", next, first)
+    }
+}
+
+impl fmt::Display for Episode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S{:02}E{:02}", self.season, self.episode)
+    }
+}
+
+/// Splits `/bolum_izledim` input into the series name and the trailing
+/// episode code.
+pub fn split_episode_arg(arg: &str) -> Option<(&str, Episode)> {
+    let (name, code) = arg.trim().rsplit_once(char::is_whitespace)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, Episode::parse(code)?))
+}
+
+/// Splits `/sezon_bitti` input into the series name and an optional trailing
+/// season number.
+pub fn split_season_arg(arg: &str) -> (&str, Option<u32>) {
+    let arg = arg.trim();
+    if let Some((name, last)) = arg.rsplit_once(char::is_whitespace) {
+        if let Ok(season) = last.parse() {
+            if !name.trim().is_empty() {
+                return (name.trim(), Some(season));
+            }
+        }
+    }
+    (arg, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_episode_codes() {
+        let expected = Episode {
+            season: 2,
+            episode: 5,
+        };
+        assert_eq!(Episode::parse("S02E05"), Some(expected));
+        assert_eq!(Episode::parse("s2e5"), Some(expected));
+        assert_eq!(Episode::parse("2x05"), Some(expected));
+        assert_eq!(Episode::parse("S00E01"), None);
+        assert_eq!(Episode::parse("2. sezon"), None);
+        assert_eq!(expected.to_string(), "S02E05");
+    }
+
+    #[test]
+    fn next_episode_hint_allows_for_the_season_ending() {
+        let episode = |season, episode| Episode { season, episode };
+        assert_eq!(episode(2, 0).up_next(Some(3)), "S02E01");
+        assert_eq!(
+            episode(2, 5).up_next(Some(3)),
+            "S02E06, sezon bittiyse `/sezon_bitti` ile S03E01"
+        );
+        assert_eq!(
+            episode(3, 5).up_next(Some(3)),
+            "S03E06 (son sezon; bittiyse `/sezon_bitti`)"
+        );
+    }
+
+    #[test]
+    fn splits_series_name_from_arguments() {
+        let (name, episode) = split_episode_arg("9-1-1 (9 Sezon) S02E05").unwrap();
+        assert_eq!(name, "9-1-1 (9 Sezon)");
+        assert_eq!(episode.season, 2);
+        assert_eq!(split_season_arg("Dark 2"), ("Dark", Some(2)));
+        assert_eq!(split_season_arg("Dark"), ("Dark", None));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::normalize::single_line;
+use std::fmt;
+
+/// A personal 1–10 score with an optional free-text note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rating {
+    pub score: u8,
+    pub note: Option<String>,
+}
+
+impl Rating {
+    pub fn new(score: u8, note: Option<String>) -> Option<Self> {
+        if !(1..=10).contains(&score) {
+            return None;
+        }
+        // Notes end up on a single line in the text backend.
+        let note = note
+            .map(|n| {
+                single_line(&n)
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|n| !n.is_empty());
+        Some(Rating { score, note })
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "⭐ {}/10", self.score)?;
+        if let Some(note) = &self.note {
+            write!(f, " · \"{}\"", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// `Matilda (1996) | 8 | Çok güzeldi` split into its parts. Score and note
+/// are optional; the score is kept as text so the caller can report a bad one.
+pub struct RatedInput<'a> {
+    pub name: &'a str,
+    pub score: Option<&'a str>,
+    pub note: Option<&'a str>,
+}
+
+pub fn split_rating_arg(arg: &str) -> RatedInput<'_> {
+    let mut parts = arg.splitn(3, '|').map(str::trim);
+    RatedInput {
+        name: parts.next().unwrap_or(""),
+        score: parts.next().filter(|s| !s.is_empty()),
+        note: parts.next().filter(|s| !s.is_empty()),
+    }
+}
+
+impl RatedInput<'_> {
+    /// `Ok(None)` when no score was given, `Err` when it is not 1–10.
+    pub fn rating(&self) -> Result<Option<Rating>, String> {
+        let Some(score) = self.score else {
+            return Ok(None);
+        };
+        score
+            .parse::<u8>()
+            .ok()
+            .and_then(|s| Rating::new(s, self.note.map(str::to_string)))
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "'{}' geçerli bir puan değil, 1 ile 10 arasında olmalı.",
+                    score
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_piped_score_counts_and_it_must_be_1_to_10() {
+        // Without `|` a trailing number is part of the title.
+        let input = split_rating_arg("Dark 8 harika");
+        assert_eq!((input.name, input.score), ("Dark 8 harika", None));
+        assert_eq!(input.rating(), Ok(None));
+
+        let input = split_rating_arg("Ocean's 8 (2018) | 7 |  çok\n  eğlenceli ");
+        assert_eq!(input.name, "Ocean's 8 (2018)");
+        assert_eq!(
+            input.rating(),
+            Ok(Some(Rating {
+                score: 7,
+                note: Some("çok eğlenceli".into())
+            }))
+        );
+        assert_eq!(
+            split_rating_arg("Dark | 10 |")
+                .rating()
+                .unwrap()
+                .unwrap()
+                .note,
+            None
+        );
+
+        for score in ["0", "11", "8.5", "on"] {
+            let arg = format!("Dark | {}", score);
+            assert!(split_rating_arg(&arg).rating().is_err(), "{}", score);
+        }
+        assert_eq!(Rating::new(0, None), None);
+    }
+}
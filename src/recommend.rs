@@ -0,0 +1,267 @@
+use crate::rating::Rating;
+use crate::status::Status;
+use crate::storage::Kind;
+use crate::title::Title;
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// How many recent suggestions per chat are not repeated, unless set with
+/// `ONERI_TEKRAR_PENCERESI`.
+const DEFAULT_WINDOW: usize = 5;
+/// Days after which a title's time in the list stops adding weight.
+const MAX_AGE_DAYS: i64 = 180;
+
+/// An unwatched title that may be suggested.
+pub struct Candidate {
+    pub title: Title,
+    pub status: Option<Status>,
+    /// `None` for lines added by hand, which are treated as old.
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+/// Average rating given to watched titles carrying each tag.
+#[derive(Default)]
+pub struct Taste {
+    tags: HashMap<String, f64>,
+}
+
+impl Taste {
+    /// Ratings are stored without tags, so they are looked up in the
+    /// catalogue.
+    pub fn from_ratings(catalogue: &[Title], ratings: &[(Title, Rating)]) -> Self {
+        let catalogue: HashSet<&Title> = catalogue.iter().collect();
+        let mut totals: HashMap<&str, (f64, f64)> = HashMap::new();
+        for (title, rating) in ratings {
+            let Some(entry) = catalogue.get(title) else {
+                continue;
+            };
+            for tag in &entry.tags {
+                let total = totals.entry(tag).or_insert((0.0, 0.0));
+                total.0 += f64::from(rating.score);
+                total.1 += 1.0;
+            }
+        }
+        Taste {
+            tags: totals
+                .into_iter()
+                .map(|(tag, (sum, count))| (tag.to_string(), sum / count))
+                .collect(),
+        }
+    }
+
+    /// Above 1 for titles whose tags were rated well, below 1 for poorly
+    /// rated ones, 1 when nothing similar was rated.
+    fn factor(&self, title: &Title) -> f64 {
+        let scores: Vec<f64> = title
+            .tags
+            .iter()
+            .filter_map(|tag| self.tags.get(tag).copied())
+            .collect();
+        if scores.is_empty() {
+            return 1.0;
+        }
+        let average = scores.iter().sum::<f64>() / scores.len() as f64;
+        (average / 5.5).clamp(0.25, 2.0)
+    }
+}
+
+/// How likely a candidate is to be picked relative to the others: planned
+/// titles, titles that have waited long and titles similar to well rated
+/// ones come up more often.
+pub fn weight(candidate: &Candidate, taste: &Taste, now: DateTime<Utc>) -> f64 {
+    let priority = match candidate.status {
+        Some(Status::Planned) => 2.0,
+        _ => 1.0,
+    };
+    let days = candidate.added_at.map_or(MAX_AGE_DAYS, |at| {
+        (now - at).num_days().clamp(0, MAX_AGE_DAYS)
+    });
+    let age = 1.0 + 2.0 * days as f64 / MAX_AGE_DAYS as f64;
+    priority * age * taste.factor(&candidate.title)
+}
+
+struct State {
+    rng: StdRng,
+    recent: HashMap<(i64, Kind), VecDeque<String>>,
+}
+
+/// Picks suggestions and remembers the last few per chat so they are not
+/// repeated. Shared by all handlers like [`crate::library::Library`].
+#[derive(Clone)]
+pub struct Recommender {
+    window: usize,
+    inner: Arc<Mutex<State>>,
+}
+
+impl Recommender {
+    /// Reads the window from `ONERI_TEKRAR_PENCERESI` and, for reproducible
+    /// runs, a seed from `ONERI_TOHUMU`.
+    pub fn from_env() -> Self {
+        let window = env::var("ONERI_TEKRAR_PENCERESI")
+            .ok()
+            .and_then(|w| w.trim().parse().ok())
+            .unwrap_or(DEFAULT_WINDOW);
+        match env::var("ONERI_TOHUMU")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+        {
+            Some(seed) => Recommender::with_seed(window, seed),
+            None => Recommender::with_rng(window, StdRng::from_entropy()),
+        }
+    }
+
+    pub fn with_seed(window: usize, seed: u64) -> Self {
+        Recommender::with_rng(window, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(window: usize, rng: StdRng) -> Self {
+        Recommender {
+            window,
+            inner: Arc::new(Mutex::new(State {
+                rng,
+                recent: HashMap::new(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Picks up to `count` different candidates by weight, skipping the
+    /// chat's recent suggestions unless nothing else is left.
+    pub fn pick(
+        &self,
+        chat: i64,
+        kind: Kind,
+        candidates: &[Candidate],
+        taste: &Taste,
+        now: DateTime<Utc>,
+        count: usize,
+    ) -> Vec<Title> {
+        let mut state = self.lock();
+        let State { rng, recent } = &mut *state;
+        let recent = recent.entry((chat, kind)).or_default();
+
+        let mut picked: Vec<Title> = Vec::new();
+        while picked.len() < count {
+            let unpicked: Vec<&Candidate> = candidates
+                .iter()
+                .filter(|c| !picked.contains(&c.title))
+                .collect();
+            let fresh: Vec<&Candidate> = unpicked
+                .iter()
+                .copied()
+                .filter(|c| !recent.contains(&c.title.to_string()))
+                .collect();
+            let pool = if fresh.is_empty() { unpicked } else { fresh };
+            let Ok(choice) = pool.choose_weighted(rng, |c| weight(c, taste, now)) else {
+                break;
+            };
+
+            recent.push_back(choice.title.to_string());
+            while recent.len() > self.window {
+                recent.pop_front();
+            }
+            picked.push(choice.title.clone());
+        }
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(line: &str) -> Candidate {
+        Candidate {
+            title: Title::parse(line),
+            status: None,
+            added_at: None,
+        }
+    }
+
+    #[test]
+    fn recent_suggestions_are_not_repeated() {
+        let candidates: Vec<Candidate> = ["A (2001)", "B (2002)", "C (2003)"]
+            .into_iter()
+            .map(candidate)
+            .collect();
+        let recommender = Recommender::with_seed(2, 7);
+        let picks: Vec<Title> = (0..9)
+            .flat_map(|_| {
+                recommender.pick(1, Kind::Film, &candidates, &Taste::default(), Utc::now(), 1)
+            })
+            .collect();
+        assert_eq!(picks.len(), 9);
+        for run in picks.windows(3) {
+            assert_ne!(run[0], run[1]);
+            assert_ne!(run[0], run[2]);
+            assert_ne!(run[1], run[2]);
+        }
+
+        // The same seed gives the same picks.
+        let again = Recommender::with_seed(2, 7);
+        for pick in &picks {
+            let title = again.pick(1, Kind::Film, &candidates, &Taste::default(), Utc::now(), 1);
+            assert_eq!(title.first(), Some(pick));
+        }
+    }
+
+    #[test]
+    fn several_picks_are_all_different() {
+        let candidates: Vec<Candidate> = ["A (2001)", "B (2002)", "C (2003)"]
+            .into_iter()
+            .map(candidate)
+            .collect();
+        let recommender = Recommender::with_seed(0, 3);
+        let picks = recommender.pick(1, Kind::Film, &candidates, &Taste::default(), Utc::now(), 5);
+        assert_eq!(picks.len(), 3);
+        assert!(picks
+            .iter()
+            .all(|p| picks.iter().filter(|q| *q == p).count() == 1));
+    }
+
+    #[test]
+    fn weights_favour_planned_old_and_well_liked_titles() {
+        let now = Utc::now();
+        let catalogue = vec![
+            Title::parse("Korku 1 (2001) | korku"),
+            Title::parse("Komedi 1 (2002) | komedi"),
+        ];
+        let ratings = vec![
+            (
+                Title::parse("Korku 1 (2001)"),
+                Rating::new(10, None).unwrap(),
+            ),
+            (
+                Title::parse("Komedi 1 (2002)"),
+                Rating::new(2, None).unwrap(),
+            ),
+        ];
+        let taste = Taste::from_ratings(&catalogue, &ratings);
+
+        let fresh = Candidate {
+            added_at: Some(now),
+            ..candidate("Yeni (2020)")
+        };
+        let planned = Candidate {
+            status: Some(Status::Planned),
+            ..candidate("Yeni (2020)")
+        };
+        let old = candidate("Eski (2020)");
+        let scary = candidate("Korku 2 (2005) | korku");
+        let funny = candidate("Komedi 2 (2006) | komedi");
+
+        assert_eq!(weight(&fresh, &taste, now), 1.0);
+        assert_eq!(weight(&old, &taste, now), 3.0);
+        assert_eq!(weight(&planned, &taste, now), 6.0);
+        assert!(weight(&scary, &taste, now) > weight(&old, &taste, now));
+        assert!(weight(&funny, &taste, now) < weight(&old, &taste, now));
+    }
+}
@@ -0,0 +1,279 @@
+use crate::history::WatchEvent;
+use crate::rating::Rating;
+use crate::storage::Kind;
+use crate::title::Title;
+use chrono::{Datelike, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// How many of the year's best rated titles the review lists.
+pub const TOP_RATED: usize = 5;
+
+/// Longest title the image card fits on a line.
+const CARD_TITLE_CHARS: usize = 40;
+
+/// A longest run of consecutive days with at least one watch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Streak {
+    pub start: NaiveDate,
+    pub days: usize,
+}
+
+/// The "Yıl Özeti" of one namespace, built from the dated watch history.
+/// Watches from before the history existed have no date and are left out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct YearReview {
+    pub year: i32,
+    /// Different titles finished, rewatches counted once.
+    pub films: usize,
+    pub series: usize,
+    /// Every watch, rewatches included.
+    pub watches: usize,
+    pub top_rated: Vec<(Kind, String, Rating)>,
+    pub streak: Streak,
+    pub top_tag: Option<(String, usize)>,
+    pub first: WatchEvent,
+    pub last: WatchEvent,
+}
+
+impl YearReview {
+    /// `None` when nothing was watched in `year`. Ratings and catalogues are
+    /// given per kind; tags come from the catalogue.
+    pub fn compute(
+        year: i32,
+        history: &[WatchEvent],
+        ratings: &HashMap<Kind, Vec<(Title, Rating)>>,
+        catalogues: &HashMap<Kind, Vec<Title>>,
+    ) -> Option<Self> {
+        let mut events: Vec<&WatchEvent> = history
+            .iter()
+            .filter(|e| e.at.with_timezone(&Local).year() == year)
+            .collect();
+        events.sort_by_key(|e| e.at);
+        let (first, last) = (*events.first()?, *events.last()?);
+
+        let titles: HashSet<(Kind, &str)> =
+            events.iter().map(|e| (e.kind, e.title.as_str())).collect();
+        let count = |kind| titles.iter().filter(|(k, _)| *k == kind).count();
+
+        let mut top_rated: Vec<(Kind, String, Rating)> = Vec::new();
+        for (kind, rated) in ratings {
+            for (title, rating) in rated {
+                let title = title.to_string();
+                if titles.contains(&(*kind, title.as_str())) {
+                    top_rated.push((*kind, title, rating.clone()));
+                }
+            }
+        }
+        top_rated.sort_by(|a, b| b.2.score.cmp(&a.2.score).then_with(|| a.1.cmp(&b.1)));
+        top_rated.truncate(TOP_RATED);
+
+        let days: Vec<NaiveDate> = events
+            .iter()
+            .map(|e| e.at.with_timezone(&Local).date_naive())
+            .collect();
+
+        Some(YearReview {
+            year,
+            films: count(Kind::Film),
+            series: count(Kind::Series),
+            watches: events.len(),
+            top_rated,
+            streak: longest_streak(&days),
+            top_tag: top_tag(&events, catalogues),
+            first: first.clone(),
+            last: last.clone(),
+        })
+    }
+}
+
+/// `days` must be sorted; repeated days count once.
+fn longest_streak(days: &[NaiveDate]) -> Streak {
+    let mut best = Streak {
+        start: days[0],
+        days: 1,
+    };
+    let mut current = best;
+    for pair in days.windows(2) {
+        let gap = (pair[1] - pair[0]).num_days();
+        if gap == 1 {
+            current.days += 1;
+        } else if gap > 1 {
+            current = Streak {
+                start: pair[1],
+                days: 1,
+            };
+        }
+        if current.days > best.days {
+            best = current;
+        }
+    }
+    best
+}
+
+/// The tag watched most often, counting every watch; ties go to the tag
+/// that comes first alphabetically.
+fn top_tag(
+    events: &[&WatchEvent],
+    catalogues: &HashMap<Kind, Vec<Title>>,
+) -> Option<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for event in events {
+        let watched = Title::parse(&event.title);
+        let Some(entry) = catalogues
+            .get(&event.kind)
+            .and_then(|titles| titles.iter().find(|t| **t == watched))
+        else {
+            continue;
+        };
+        for tag in &entry.tags {
+            *counts.entry(tag).or_insert(0) += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(tag, count)| (tag.to_string(), count))
+}
+
+impl YearReview {
+    /// Plain lines for the image card, which has no emoji font.
+    pub fn card_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "{} film, {} dizi bitirdin ({} izleme)",
+                self.films, self.series, self.watches
+            ),
+            format!("En uzun seri: {} gün üst üste", self.streak.days),
+        ];
+        if let Some((tag, count)) = &self.top_tag {
+            lines.push(format!("En çok izlenen tür: {} ({} kez)", tag, count));
+        }
+        for (i, (_, title, rating)) in self.top_rated.iter().enumerate() {
+            lines.push(format!(
+                "{}. {} - {}/10",
+                i + 1,
+                shorten(title),
+                rating.score
+            ));
+        }
+        lines.push(format!("İlk: {}", shorten(&self.first.title)));
+        lines.push(format!("Son: {}", shorten(&self.last.title)));
+        lines
+    }
+}
+
+fn shorten(title: &str) -> String {
+    if title.chars().count() <= CARD_TITLE_CHARS {
+        return title.to_string();
+    }
+    let cut: String = title.chars().take(CARD_TITLE_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+impl fmt::Display for YearReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🎉 {} Yıl Özetin", self.year)?;
+        writeln!(
+            f,
+            "🎬 {} film ve 📺 {} dizi bitirdin, toplam {} izleme.",
+            self.films, self.series, self.watches
+        )?;
+        if !self.top_rated.is_empty() {
+            writeln!(f, "\n🏆 En yüksek puan verdiklerin:")?;
+            for (i, (kind, title, rating)) in self.top_rated.iter().enumerate() {
+                writeln!(f, "{}. {} {} — {}", i + 1, kind.emoji(), title, rating)?;
+            }
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "🔥 En uzun serin: {} gün üst üste ({} itibarıyla)",
+            self.streak.days,
+            self.streak.start.format("%d.%m.%Y")
+        )?;
+        if let Some((tag, count)) = &self.top_tag {
+            writeln!(f, "🏷️ En çok izlediğin tür: {} ({} kez)", tag, count)?;
+        }
+        writeln!(
+            f,
+            "▶️ Yılın ilk izlemesi: {} {} ({})",
+            self.first.kind.emoji(),
+            self.first.title,
+            self.first.local_date()
+        )?;
+        writeln!(
+            f,
+            "⏹️ Yılın son izlemesi: {} {} ({})",
+            self.last.kind.emoji(),
+            self.last.title,
+            self.last.local_date()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn watch(kind: Kind, title: &str, (y, m, d): (i32, u32, u32)) -> WatchEvent {
+        WatchEvent {
+            kind,
+            title: title.into(),
+            at: Local.with_ymd_and_hms(y, m, d, 20, 0, 0).unwrap().to_utc(),
+        }
+    }
+
+    #[test]
+    fn reviews_a_year_of_watches() {
+        let history = vec![
+            watch(Kind::Film, "Eski (1990)", (2025, 12, 31)),
+            watch(Kind::Film, "Matilda (1996)", (2026, 1, 3)),
+            watch(Kind::Film, "Korku (2014)", (2026, 2, 1)),
+            watch(Kind::Film, "Korku (2014)", (2026, 2, 2)),
+            watch(Kind::Series, "Dark (3 Sezon)", (2026, 2, 3)),
+            watch(Kind::Film, "It (2017)", (2026, 2, 3)),
+            watch(Kind::Film, "Matilda (1996)", (2026, 6, 9)),
+        ];
+        let ratings = HashMap::from([(
+            Kind::Film,
+            vec![
+                (
+                    Title::parse("Matilda (1996)"),
+                    Rating::new(9, None).unwrap(),
+                ),
+                (Title::parse("Eski (1990)"), Rating::new(10, None).unwrap()),
+                (Title::parse("Korku (2014)"), Rating::new(7, None).unwrap()),
+            ],
+        )]);
+        let catalogues = HashMap::from([
+            (
+                Kind::Film,
+                vec![
+                    Title::parse("Korku (2014) | korku"),
+                    Title::parse("It (2017) | korku, gerilim"),
+                    Title::parse("Matilda (1996) | komedi"),
+                ],
+            ),
+            (Kind::Series, vec![Title::parse("Dark (3 Sezon) | gerilim")]),
+        ]);
+
+        let review = YearReview::compute(2026, &history, &ratings, &catalogues).unwrap();
+        assert_eq!((review.films, review.series, review.watches), (3, 1, 6));
+        let top: Vec<&str> = review.top_rated.iter().map(|r| r.1.as_str()).collect();
+        assert_eq!(top, vec!["Matilda (1996)", "Korku (2014)"]);
+        assert_eq!(
+            review.streak,
+            Streak {
+                start: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                days: 3
+            }
+        );
+        assert_eq!(review.top_tag, Some(("korku".to_string(), 3)));
+        assert_eq!(review.first.title, "Matilda (1996)");
+        assert_eq!(review.last.at, history[6].at);
+
+        assert!(YearReview::compute(2024, &history, &ratings, &catalogues).is_none());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::filter::parse_years;
+use crate::normalize::fold;
+use crate::storage::Kind;
+use crate::title::Title;
+
+/// What `/ara` was asked for: free words, each of which has to appear
+/// somewhere in the line or its tags, plus `tur:`, `yil:` and `izlendi:`
+/// filters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Folded, so matching ignores case and Turkish diacritics.
+    pub words: Vec<String>,
+    pub kind: Option<Kind>,
+    pub years: Option<(u16, u16)>,
+    pub watched: Option<bool>,
+}
+
+impl SearchQuery {
+    pub fn parse(arg: &str) -> Result<Self, String> {
+        let mut query = SearchQuery::default();
+        for word in arg.split_whitespace() {
+            let Some((key, value)) = word.split_once(':') else {
+                query.words.push(fold(word));
+                continue;
+            };
+            let value = fold(value);
+            match fold(key).as_str() {
+                "tur" => {
+                    query.kind = Some(Kind::from_key(&value).ok_or_else(|| {
+                        format!("'{}' bir tür değil, film veya dizi olmalı", value)
+                    })?)
+                }
+                "yil" => {
+                    query.years = Some(parse_years(&value)?.ok_or_else(|| {
+                        format!("'{}' bir yıl değil, 2019 veya 2010-2020 gibi olmalı", value)
+                    })?)
+                }
+                "izlendi" => {
+                    query.watched = Some(match value.as_str() {
+                        "evet" => true,
+                        "hayir" => false,
+                        _ => {
+                            return Err(format!(
+                                "'{}' anlaşılmadı, izlendi:evet veya izlendi:hayir yaz",
+                                value
+                            ))
+                        }
+                    })
+                }
+                _ => return Err(format!("'{}' diye bir filtre yok", key)),
+            }
+        }
+        if query.words.is_empty()
+            && query.kind.is_none()
+            && query.years.is_none()
+            && query.watched.is_none()
+        {
+            return Err("ne arayacağımı yazmadın".into());
+        }
+        Ok(query)
+    }
+
+    /// The years filter leaves series out, since they carry no year.
+    pub fn matches(&self, kind: Kind, title: &Title, watched: bool) -> bool {
+        if self.kind.is_some_and(|k| k != kind) || self.watched.is_some_and(|w| w != watched) {
+            return false;
+        }
+        if let Some((from, to)) = self.years {
+            if !title.year.is_some_and(|y| (from..=to).contains(&y)) {
+                return false;
+            }
+        }
+        let haystack = fold(&title.line());
+        self.words
+            .iter()
+            .all(|word| haystack.contains(word.as_str()))
+    }
+
+    /// The free words joined back up, for ranking and fuzzy fallbacks.
+    pub fn text(&self) -> String {
+        self.words.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_filters_and_matches_partial_words() {
+        let query = SearchQuery::parse("YÜZÜK efen tur:film yil:2000-2005 izlendi:hayır").unwrap();
+        assert_eq!(query.words, vec!["yuzuk", "efen"]);
+        assert_eq!(query.kind, Some(Kind::Film));
+        assert_eq!(query.years, Some((2000, 2005)));
+        assert_eq!(query.watched, Some(false));
+
+        let lotr = Title::parse("Yüzüklerin Efendisi (2001) | fantastik");
+        assert!(query.matches(Kind::Film, &lotr, false));
+        assert!(!query.matches(Kind::Film, &lotr, true));
+        assert!(!query.matches(Kind::Series, &lotr, false));
+
+        let tagged = SearchQuery::parse("fanta").unwrap();
+        assert!(tagged.matches(Kind::Film, &lotr, true));
+
+        assert!(SearchQuery::parse("tur:kitap").is_err());
+        assert!(SearchQuery::parse("renk:mavi").is_err());
+        assert!(SearchQuery::parse("  ").is_err());
+    }
+}
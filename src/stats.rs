@@ -0,0 +1,262 @@
+use crate::history::WatchEvent;
+use crate::progress::Episode;
+use crate::storage::Kind;
+use crate::title::Title;
+use chrono::{DateTime, Datelike, Local, Months, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// Months shown in the watches-per-month breakdown, ending with this one.
+pub const MONTHS_SHOWN: u32 = 12;
+/// Width of the longest bar in the text breakdowns.
+const BAR_WIDTH: usize = 12;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub total: usize,
+    pub watched: usize,
+}
+
+impl Progress {
+    pub fn unwatched(&self) -> usize {
+        self.total - self.watched
+    }
+
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        100.0 * self.watched as f64 / self.total as f64
+    }
+}
+
+/// How far through the catalogue a namespace is. Watched entries that left
+/// the catalogue are not counted, so the numbers always add up.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub films: Progress,
+    pub series: Progress,
+    /// Films by decade of release, e.g. 1990 for the nineties.
+    pub decades: BTreeMap<u16, Progress>,
+    /// Films without a `(YYYY)` year.
+    pub undated: usize,
+    /// Seasons of unwatched series still ahead, minus seasons already
+    /// finished in series in progress.
+    pub seasons_left: u32,
+    /// Watches per month as `(year, month, count)`, oldest first; empty until
+    /// something was watched with a date.
+    pub monthly: Vec<(i32, u32, usize)>,
+}
+
+/// What a namespace's statistics are computed from, for one kind.
+pub struct Catalogue<'a> {
+    pub titles: &'a [Title],
+    pub watched: &'a HashSet<String>,
+}
+
+impl Stats {
+    pub fn compute(
+        films: Catalogue,
+        series: Catalogue,
+        progress: &HashMap<String, Episode>,
+        history: &[WatchEvent],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut stats = Stats {
+            films: progress_of(&films),
+            series: progress_of(&series),
+            ..Stats::default()
+        };
+
+        for title in films.titles {
+            let watched = films.watched.contains(&title.to_string());
+            let Some(year) = title.year else {
+                stats.undated += 1;
+                continue;
+            };
+            let decade = stats.decades.entry(year / 10 * 10).or_default();
+            decade.total += 1;
+            decade.watched += usize::from(watched);
+        }
+
+        for title in series.titles {
+            let key = title.to_string();
+            if series.watched.contains(&key) {
+                continue;
+            }
+            let seasons = title.seasons.unwrap_or(0);
+            let finished = progress.get(&key).map_or(0, |e| e.season.saturating_sub(1));
+            stats.seasons_left += seasons.saturating_sub(finished);
+        }
+
+        if !history.is_empty() {
+            let mut counts: HashMap<(i32, u32), usize> = HashMap::new();
+            for event in history {
+                let at = event.at.with_timezone(&Local);
+                *counts.entry((at.year(), at.month())).or_insert(0) += 1;
+            }
+            let this_month = now.with_timezone(&Local).date_naive().with_day(1).unwrap();
+            stats.monthly = (0..MONTHS_SHOWN)
+                .rev()
+                .filter_map(|back| this_month.checked_sub_months(Months::new(back)))
+                .map(|month| {
+                    let key = (month.year(), month.month());
+                    (key.0, key.1, counts.get(&key).copied().unwrap_or(0))
+                })
+                .collect();
+        }
+        stats
+    }
+}
+
+fn progress_of(catalogue: &Catalogue) -> Progress {
+    Progress {
+        total: catalogue.titles.len(),
+        watched: catalogue
+            .titles
+            .iter()
+            .filter(|t| catalogue.watched.contains(&t.to_string()))
+            .count(),
+    }
+}
+
+fn bar(value: usize, max: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    "█".repeat((value * BAR_WIDTH).div_ceil(max))
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "📊 İstatistiklerin:")?;
+        for (kind, progress) in [(Kind::Film, self.films), (Kind::Series, self.series)] {
+            writeln!(
+                f,
+                "{} {}: {} toplam, {} izlendi, {} kaldı (%{:.0})",
+                kind.emoji(),
+                kind.title_plural(),
+                progress.total,
+                progress.watched,
+                progress.unwatched(),
+                progress.percent()
+            )?;
+        }
+        writeln!(
+            f,
+            "⏳ İzlenmemiş dizilerde kalan sezon: {}",
+            self.seasons_left
+        )?;
+
+        if !self.decades.is_empty() {
+            writeln!(f, "\n📅 Yıllara göre filmler (izlenen/toplam):")?;
+            let max = self.decades.values().map(|d| d.total).max().unwrap_or(0);
+            for (decade, progress) in &self.decades {
+                writeln!(
+                    f,
+                    "{}'ler {} {}/{}",
+                    decade,
+                    bar(progress.total, max),
+                    progress.watched,
+                    progress.total
+                )?;
+            }
+            if self.undated > 0 {
+                writeln!(f, "Yılı belirsiz: {}", self.undated)?;
+            }
+        }
+
+        if !self.monthly.is_empty() {
+            writeln!(f, "\n🗓️ Aylara göre izlemeler:")?;
+            let max = self.monthly.iter().map(|m| m.2).max().unwrap_or(0);
+            for (year, month, count) in &self.monthly {
+                writeln!(f, "{:02}.{} {} {}", month, year, bar(*count, max), count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn counts_progress_decades_seasons_and_months() {
+        let films: Vec<Title> = ["A (1994)", "B (1999)", "C (2012)", "D"]
+            .into_iter()
+            .map(Title::parse)
+            .collect();
+        let series: Vec<Title> = ["X (3 Sezon)", "Y (2 Sezon)", "Z (5 Sezon)"]
+            .into_iter()
+            .map(Title::parse)
+            .collect();
+        let watched_films: HashSet<String> = ["A (1994)", "Gone (2001)"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let watched_series: HashSet<String> = ["Z (5 Sezon)".to_string()].into();
+        let progress = HashMap::from([(
+            "X (3 Sezon)".to_string(),
+            Episode {
+                season: 2,
+                episode: 4,
+            },
+        )]);
+        let now = Local
+            .with_ymd_and_hms(2026, 3, 15, 12, 0, 0)
+            .unwrap()
+            .to_utc();
+        let history = vec![
+            WatchEvent {
+                kind: Kind::Film,
+                title: "A (1994)".into(),
+                at: now,
+            },
+            WatchEvent {
+                kind: Kind::Series,
+                title: "Z (5 Sezon)".into(),
+                at: Local
+                    .with_ymd_and_hms(2026, 1, 2, 12, 0, 0)
+                    .unwrap()
+                    .to_utc(),
+            },
+        ];
+
+        let stats = Stats::compute(
+            Catalogue {
+                titles: &films,
+                watched: &watched_films,
+            },
+            Catalogue {
+                titles: &series,
+                watched: &watched_series,
+            },
+            &progress,
+            &history,
+            now,
+        );
+        assert_eq!(
+            stats.films,
+            Progress {
+                total: 4,
+                watched: 1
+            }
+        );
+        assert_eq!(stats.series.unwatched(), 2);
+        assert_eq!(
+            stats.decades[&1990],
+            Progress {
+                total: 2,
+                watched: 1
+            }
+        );
+        assert_eq!(stats.undated, 1);
+        assert_eq!(stats.seasons_left, 2 + 2);
+        assert_eq!(stats.monthly.len(), MONTHS_SHOWN as usize);
+        assert_eq!(stats.monthly.last(), Some(&(2026, 3, 1)));
+        assert_eq!(stats.monthly[stats.monthly.len() - 3], (2026, 1, 1));
+        assert!(stats.to_string().contains("1990'ler"));
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+
+/// Where a title stands for one namespace. `Completed` is not stored on its
+/// own: it is the same thing as being in the watched list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Status {
+    Planned,
+    Watching,
+    Paused,
+    Dropped,
+    Completed,
+}
+
+impl Status {
+    pub const ALL: [Status; 5] = [
+        Status::Watching,
+        Status::Paused,
+        Status::Planned,
+        Status::Dropped,
+        Status::Completed,
+    ];
+
+    /// Stable key used in storage.
+    pub fn key(self) -> &'static str {
+        match self {
+            Status::Planned => "planlandi",
+            Status::Watching => "izleniyor",
+            Status::Paused => "beklemede",
+            Status::Dropped => "birakildi",
+            Status::Completed => "tamamlandi",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Status::ALL.into_iter().find(|s| s.key() == key)
+    }
+
+    /// Parses what users type after `/film_durum <Ad>`: `None` for an unknown
+    /// word, `Some(None)` for `yok`, which clears the status.
+    pub fn parse_arg(arg: &str) -> Option<Option<Self>> {
+        match arg.trim().to_lowercase().as_str() {
+            "planli" | "planlı" | "planlandi" | "planlandı" | "plan" => {
+                Some(Some(Status::Planned))
+            }
+            "izliyorum" | "izleniyor" => Some(Some(Status::Watching)),
+            "beklemede" | "beklet" | "ara" => Some(Some(Status::Paused)),
+            "birakildi" | "bırakıldı" | "biraktim" | "bıraktım" | "birak" | "bırak" => {
+                Some(Some(Status::Dropped))
+            }
+            "tamamlandi" | "tamamlandı" | "bitti" | "izlendi" => Some(Some(Status::Completed)),
+            "yok" | "sil" => Some(None),
+            _ => None,
+        }
+    }
+
+    pub fn is_in_progress(self) -> bool {
+        matches!(self, Status::Watching | Status::Paused)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Planned => "📝 Planlandı",
+            Status::Watching => "▶️ İzleniyor",
+            Status::Paused => "⏸️ Beklemede",
+            Status::Dropped => "🛑 Bırakıldı",
+            Status::Completed => "✅ Tamamlandı",
+        })
+    }
+}
+
+/// Splits `/film_durum` input into the title and the trailing status word.
+pub fn split_status_arg(arg: &str) -> Option<(&str, &str)> {
+    let (name, status) = arg.trim().rsplit_once(char::is_whitespace)?;
+    let name = name.trim();
+    (!name.is_empty()).then_some((name, status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status_words_and_splits_them_off() {
+        assert_eq!(
+            Status::parse_arg(" izliyorum "),
+            Some(Some(Status::Watching))
+        );
+        assert_eq!(Status::parse_arg("BIRAKILDI"), Some(Some(Status::Dropped)));
+        assert_eq!(Status::parse_arg("bıraktım"), Some(Some(Status::Dropped)));
+        assert_eq!(Status::parse_arg("yok"), Some(None));
+        assert_eq!(Status::parse_arg("belki"), None);
+        for status in Status::ALL {
+            assert_eq!(Status::from_key(status.key()), Some(status));
+        }
+
+        assert_eq!(
+            split_status_arg("Ocean's 8 (2018) bitti"),
+            Some(("Ocean's 8 (2018)", "bitti"))
+        );
+        assert_eq!(split_status_arg("bitti"), None);
+    }
+}
@@ -0,0 +1,259 @@
+use super::{Kind, Storage};
+use crate::history::WatchEvent;
+use crate::journal::Entry;
+use crate::namespace::Namespace;
+use crate::progress::Episode;
+use crate::rating::Rating;
+use crate::status::Status;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Default)]
+struct Cache {
+    watched: HashMap<(Namespace, Kind), HashSet<String>>,
+    statuses: HashMap<(Namespace, Kind), HashMap<String, Status>>,
+    ratings: HashMap<(Namespace, Kind), HashMap<String, Rating>>,
+    progress: HashMap<Namespace, HashMap<String, Episode>>,
+    history: HashMap<Namespace, Vec<WatchEvent>>,
+    journal: HashMap<Namespace, Vec<Entry>>,
+    added: HashMap<Kind, HashMap<String, DateTime<Utc>>>,
+}
+
+/// Keeps what another backend read for each namespace in memory, so
+/// commands that only look at lists do not touch the disk. Every write goes
+/// straight to the backend and drops the list it changed, which is read
+/// again when next needed. Lists changed behind its back are not noticed;
+/// only the catalogue is meant to be edited by hand.
+pub struct CachedStorage {
+    inner: Box<dyn Storage>,
+    cache: Mutex<Cache>,
+}
+
+impl CachedStorage {
+    pub fn new(inner: Box<dyn Storage>) -> Self {
+        CachedStorage {
+            inner,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    fn cache(&self) -> MutexGuard<'_, Cache> {
+        // The cache only ever holds whole lists, so a panic cannot leave it
+        // half updated.
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drops what a write may have changed, whether it succeeded or not, and
+    /// passes its result on.
+    fn write<T>(&self, result: io::Result<T>, forget: impl FnOnce(&mut Cache)) -> io::Result<T> {
+        forget(&mut self.cache());
+        result
+    }
+}
+
+fn read_through<K: Eq + Hash, V: Clone>(
+    map: &mut HashMap<K, V>,
+    key: K,
+    read: impl FnOnce() -> io::Result<V>,
+) -> io::Result<V> {
+    if let Some(value) = map.get(&key) {
+        return Ok(value.clone());
+    }
+    let value = read()?;
+    map.insert(key, value.clone());
+    Ok(value)
+}
+
+impl Storage for CachedStorage {
+    fn catalogue(&self, kind: Kind) -> io::Result<Vec<String>> {
+        self.inner.catalogue(kind)
+    }
+
+    fn watched(&self, ns: &Namespace, kind: Kind) -> io::Result<HashSet<String>> {
+        read_through(&mut self.cache().watched, (*ns, kind), || {
+            self.inner.watched(ns, kind)
+        })
+    }
+
+    fn add(&self, kind: Kind, title: &str) -> io::Result<bool> {
+        self.inner.add(kind, title)
+    }
+
+    fn mark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool> {
+        let result = self.inner.mark_watched(ns, kind, title);
+        self.write(result, |c| drop(c.watched.remove(&(*ns, kind))))
+    }
+
+    fn unmark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool> {
+        let result = self.inner.unmark_watched(ns, kind, title);
+        self.write(result, |c| drop(c.watched.remove(&(*ns, kind))))
+    }
+
+    fn remove(&self, kind: Kind, title: &str) -> io::Result<bool> {
+        let result = self.inner.remove(kind, title);
+        self.write(result, |c| drop(c.added.remove(&kind)))
+    }
+
+    fn replace(&self, kind: Kind, old: &str, new: &str) -> io::Result<bool> {
+        self.inner.replace(kind, old, new)
+    }
+
+    fn progress(&self, ns: &Namespace) -> io::Result<HashMap<String, Episode>> {
+        read_through(&mut self.cache().progress, *ns, || self.inner.progress(ns))
+    }
+
+    fn set_progress(
+        &self,
+        ns: &Namespace,
+        title: &str,
+        episode: Option<Episode>,
+    ) -> io::Result<()> {
+        let result = self.inner.set_progress(ns, title, episode);
+        self.write(result, |c| drop(c.progress.remove(ns)))
+    }
+
+    fn statuses(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Status>> {
+        read_through(&mut self.cache().statuses, (*ns, kind), || {
+            self.inner.statuses(ns, kind)
+        })
+    }
+
+    fn set_status(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        status: Option<Status>,
+    ) -> io::Result<()> {
+        let result = self.inner.set_status(ns, kind, title, status);
+        self.write(result, |c| drop(c.statuses.remove(&(*ns, kind))))
+    }
+
+    fn ratings(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Rating>> {
+        read_through(&mut self.cache().ratings, (*ns, kind), || {
+            self.inner.ratings(ns, kind)
+        })
+    }
+
+    fn set_rating(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        rating: Option<Rating>,
+    ) -> io::Result<()> {
+        let result = self.inner.set_rating(ns, kind, title, rating);
+        self.write(result, |c| drop(c.ratings.remove(&(*ns, kind))))
+    }
+
+    fn added_dates(&self, kind: Kind) -> io::Result<HashMap<String, DateTime<Utc>>> {
+        read_through(&mut self.cache().added, kind, || {
+            self.inner.added_dates(kind)
+        })
+    }
+
+    fn record_added(&self, kind: Kind, title: &str, at: DateTime<Utc>) -> io::Result<()> {
+        let result = self.inner.record_added(kind, title, at);
+        self.write(result, |c| drop(c.added.remove(&kind)))
+    }
+
+    fn history(&self, ns: &Namespace) -> io::Result<Vec<WatchEvent>> {
+        read_through(&mut self.cache().history, *ns, || self.inner.history(ns))
+    }
+
+    fn record_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()> {
+        let result = self.inner.record_watch(ns, event);
+        self.write(result, |c| drop(c.history.remove(ns)))
+    }
+
+    fn forget_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()> {
+        let result = self.inner.forget_watch(ns, event);
+        self.write(result, |c| drop(c.history.remove(ns)))
+    }
+
+    fn forget_history(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<()> {
+        let result = self.inner.forget_history(ns, kind, title);
+        self.write(result, |c| drop(c.history.remove(ns)))
+    }
+
+    fn rename_everywhere(&self, kind: Kind, old: &str, new: &str) -> io::Result<()> {
+        let result = self.inner.rename_everywhere(kind, old, new);
+        self.write(result, |c| *c = Cache::default())
+    }
+
+    fn namespaces(&self) -> io::Result<Vec<Namespace>> {
+        self.inner.namespaces()
+    }
+
+    fn journal(&self, ns: &Namespace) -> io::Result<Vec<Entry>> {
+        read_through(&mut self.cache().journal, *ns, || self.inner.journal(ns))
+    }
+
+    fn append_journal(&self, ns: &Namespace, entry: &Entry) -> io::Result<()> {
+        let result = self.inner.append_journal(ns, entry);
+        self.write(result, |c| drop(c.journal.remove(ns)))
+    }
+
+    fn add_all(&self, kind: Kind, titles: &[String]) -> io::Result<()> {
+        self.inner.add_all(kind, titles)
+    }
+
+    fn record_added_all(&self, kind: Kind, titles: &[String], at: DateTime<Utc>) -> io::Result<()> {
+        let result = self.inner.record_added_all(kind, titles, at);
+        self.write(result, |c| drop(c.added.remove(&kind)))
+    }
+
+    fn mark_all_watched(&self, ns: &Namespace, kind: Kind, titles: &[String]) -> io::Result<()> {
+        let result = self.inner.mark_all_watched(ns, kind, titles);
+        self.write(result, |c| drop(c.watched.remove(&(*ns, kind))))
+    }
+
+    fn record_watches(&self, ns: &Namespace, events: &[WatchEvent]) -> io::Result<()> {
+        let result = self.inner.record_watches(ns, events);
+        self.write(result, |c| drop(c.history.remove(ns)))
+    }
+
+    fn append_journal_all(&self, ns: &Namespace, entries: &[Entry]) -> io::Result<()> {
+        let result = self.inner.append_journal_all(ns, entries);
+        self.write(result, |c| drop(c.journal.remove(ns)))
+    }
+
+    fn source_files(&self) -> Vec<(Kind, PathBuf)> {
+        self.inner.source_files()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::TextStorage;
+
+    #[test]
+    fn reads_come_from_memory_until_a_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = TextStorage::new(dir.path(), dir.path().join("veri"));
+        let cached = CachedStorage::new(Box::new(text));
+        let ns = Namespace::User(5);
+        let list = dir.path().join("veri/kullanici_5/izlenen_filmler.txt");
+
+        cached
+            .mark_watched(&ns, Kind::Film, "Alien (1979)")
+            .unwrap();
+        assert_eq!(cached.watched(&ns, Kind::Film).unwrap().len(), 1);
+        std::fs::write(&list, "Alien (1979)\nElle yazılan (2000)\n").unwrap();
+        assert_eq!(cached.watched(&ns, Kind::Film).unwrap().len(), 1);
+
+        cached.mark_watched(&ns, Kind::Film, "Heat (1995)").unwrap();
+        assert_eq!(cached.watched(&ns, Kind::Film).unwrap().len(), 3);
+
+        std::fs::remove_file(&list).unwrap();
+        cached
+            .rename_everywhere(Kind::Film, "Heat (1995)", "Heat (1996)")
+            .unwrap();
+        assert!(cached.watched(&ns, Kind::Film).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+mod cached;
+mod sqlite;
+mod text;
+
+use crate::history::WatchEvent;
+use crate::journal::Entry;
+use crate::namespace::Namespace;
+use crate::progress::Episode;
+use crate::rating::Rating;
+use crate::status::Status;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::io;
+use std::path::PathBuf;
+
+pub use cached::CachedStorage;
+pub use sqlite::SqliteStorage;
+pub use text::TextStorage;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Film,
+    Series,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 2] = [Kind::Film, Kind::Series];
+
+    /// Stable key used in database rows, file names and callback data; also
+    /// the lowercase noun of messages and command names (`/film_ekle`).
+    pub fn key(self) -> &'static str {
+        match self {
+            Kind::Film => "film",
+            Kind::Series => "dizi",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Kind::ALL.into_iter().find(|k| k.key() == key)
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Kind::Film => "Film",
+            Kind::Series => "Dizi",
+        }
+    }
+
+    /// "filmi" / "dizisi", as in "'X' filmi izlenenlere eklendi".
+    pub fn possessive(self) -> &'static str {
+        match self {
+            Kind::Film => "filmi",
+            Kind::Series => "dizisi",
+        }
+    }
+
+    pub fn plural(self) -> &'static str {
+        match self {
+            Kind::Film => "filmler",
+            Kind::Series => "diziler",
+        }
+    }
+
+    pub fn title_plural(self) -> &'static str {
+        match self {
+            Kind::Film => "Filmler",
+            Kind::Series => "Diziler",
+        }
+    }
+
+    /// "filmini" / "dizini", as in "ilk filmini öner".
+    pub fn yours(self) -> &'static str {
+        match self {
+            Kind::Film => "filmini",
+            Kind::Series => "dizini",
+        }
+    }
+
+    /// What a catalogue line carries after the name: the year or season count.
+    pub fn detail(self) -> &'static str {
+        match self {
+            Kind::Film => "yıl",
+            Kind::Series => "sezon",
+        }
+    }
+
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Kind::Film => "🎬",
+            Kind::Series => "📺",
+        }
+    }
+
+    pub fn catalogue_file(self) -> &'static str {
+        match self {
+            Kind::Film => "filmler.txt",
+            Kind::Series => "diziler.txt",
+        }
+    }
+
+    pub fn watched_file(self) -> &'static str {
+        match self {
+            Kind::Film => "izlenen_filmler.txt",
+            Kind::Series => "izlenen_diziler.txt",
+        }
+    }
+}
+
+/// Everything the bot persists goes through this trait so the backend can be
+/// picked at startup.
+pub trait Storage: Send + Sync {
+    fn catalogue(&self, kind: Kind) -> io::Result<Vec<String>>;
+    fn watched(&self, ns: &Namespace, kind: Kind) -> io::Result<HashSet<String>>;
+    /// Returns `false` if the title was already in the catalogue.
+    fn add(&self, kind: Kind, title: &str) -> io::Result<bool>;
+    /// Returns `false` if the title was already marked as watched.
+    fn mark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool>;
+    /// Returns `false` if the title was not marked as watched.
+    fn unmark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool>;
+    /// Returns `false` if the title was not in the catalogue.
+    fn remove(&self, kind: Kind, title: &str) -> io::Result<bool>;
+    /// Rewrites a catalogue line in place. Returns `false` if `old` was not in
+    /// the catalogue or `new` already was.
+    fn replace(&self, kind: Kind, old: &str, new: &str) -> io::Result<bool>;
+
+    /// Last watched episode of every series in progress, keyed by title.
+    fn progress(&self, ns: &Namespace) -> io::Result<HashMap<String, Episode>>;
+    /// `None` clears the series' progress.
+    fn set_progress(&self, ns: &Namespace, title: &str, episode: Option<Episode>)
+        -> io::Result<()>;
+
+    /// Explicit statuses set for titles, keyed by title. Never contains
+    /// [`Status::Completed`], which is the watched list itself.
+    fn statuses(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Status>>;
+    /// `None` clears the title's status.
+    fn set_status(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        status: Option<Status>,
+    ) -> io::Result<()>;
+
+    fn ratings(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Rating>>;
+    /// `None` clears the title's rating.
+    fn set_rating(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        rating: Option<Rating>,
+    ) -> io::Result<()>;
+
+    /// When titles were added to the catalogue through the bot, keyed by
+    /// title. Lines added by hand have no date.
+    fn added_dates(&self, kind: Kind) -> io::Result<HashMap<String, DateTime<Utc>>>;
+    fn record_added(&self, kind: Kind, title: &str, at: DateTime<Utc>) -> io::Result<()>;
+
+    /// Every recorded watch in `ns`, oldest first.
+    fn history(&self, ns: &Namespace) -> io::Result<Vec<WatchEvent>>;
+    fn record_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()>;
+
+    /// Drops one recorded watch, matched on its kind, title and second.
+    fn forget_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()>;
+    /// Drops every recorded watch of a title in `ns`.
+    fn forget_history(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<()>;
+
+    /// Moves the watched entry, status, rating, progress, history, added
+    /// date and journal entries of `old` over to `new` in every namespace.
+    fn rename_everywhere(&self, kind: Kind, old: &str, new: &str) -> io::Result<()>;
+
+    /// Every namespace that has anything stored.
+    fn namespaces(&self) -> io::Result<Vec<Namespace>>;
+
+    /// Every change journaled in `ns`, oldest first.
+    fn journal(&self, ns: &Namespace) -> io::Result<Vec<Entry>>;
+    /// Entries are appended and only ever rewritten to follow a rename, so
+    /// the journal also serves as an audit log.
+    fn append_journal(&self, ns: &Namespace, entry: &Entry) -> io::Result<()>;
+
+    // Many changes at once, as an import makes them. Backends that rewrite a
+    // whole file per change should write each file once instead.
+
+    fn add_all(&self, kind: Kind, titles: &[String]) -> io::Result<()> {
+        titles.iter().try_for_each(|t| self.add(kind, t).map(drop))
+    }
+    fn record_added_all(&self, kind: Kind, titles: &[String], at: DateTime<Utc>) -> io::Result<()> {
+        titles
+            .iter()
+            .try_for_each(|t| self.record_added(kind, t, at))
+    }
+    fn mark_all_watched(&self, ns: &Namespace, kind: Kind, titles: &[String]) -> io::Result<()> {
+        titles
+            .iter()
+            .try_for_each(|t| self.mark_watched(ns, kind, t).map(drop))
+    }
+    fn record_watches(&self, ns: &Namespace, events: &[WatchEvent]) -> io::Result<()> {
+        events.iter().try_for_each(|e| self.record_watch(ns, e))
+    }
+    fn append_journal_all(&self, ns: &Namespace, entries: &[Entry]) -> io::Result<()> {
+        entries.iter().try_for_each(|e| self.append_journal(ns, e))
+    }
+
+    /// Catalogue files that may be edited by hand while the bot is running.
+    fn source_files(&self) -> Vec<(Kind, PathBuf)> {
+        Vec::new()
+    }
+}
+
+/// Picks the backend from `DEPOLAMA` (`metin` or `sqlite`).
+pub fn from_env() -> io::Result<Box<dyn Storage>> {
+    let backend = env::var("DEPOLAMA").unwrap_or_else(|_| "metin".into());
+    let data_dir = env::var("VERI_DIZINI").unwrap_or_else(|_| "veri".into());
+    let text = TextStorage::new(".", &data_dir);
+    adopt_legacy_lists(&text)?;
+    match backend.trim() {
+        "sqlite" => {
+            let path = env::var("SQLITE_DOSYASI").unwrap_or_else(|_| "veri/kutuphane.db".into());
+            info!("Using SQLite storage at '{}'.", path);
+            let storage = SqliteStorage::open(&path)?;
+            storage.seed_from(&text)?;
+            Ok(Box::new(storage))
+        }
+        "metin" => {
+            info!("Using text file storage under '{}'.", data_dir);
+            Ok(Box::new(text))
+        }
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "bilinmeyen DEPOLAMA değeri: '{}' (metin veya sqlite olmalı)",
+                other
+            ),
+        )),
+    }
+}
+
+/// Moves the single watched list of older versions to the namespace named by
+/// `ESKI_LISTE_SAHIBI` (a Telegram user id, a negative group id or a key like
+/// `kullanici_123`). Until it is set the old files are left where they are.
+fn adopt_legacy_lists(text: &TextStorage) -> io::Result<()> {
+    let legacy = text.legacy_lists();
+    if legacy.is_empty() {
+        return Ok(());
+    }
+    match env::var("ESKI_LISTE_SAHIBI") {
+        Ok(owner) => {
+            let owner = Namespace::parse(&owner).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("ESKI_LISTE_SAHIBI anlaşılmadı: '{}'", owner),
+                )
+            })?;
+            text.adopt_legacy_lists(&owner)?;
+        }
+        Err(_) => {
+            for (_, path) in legacy {
+                warn!(
+                    "'{}' is a watched list from an older version; set ESKI_LISTE_SAHIBI to its owner's Telegram id to move it.",
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,886 @@
+use super::{Kind, Storage};
+use crate::history::WatchEvent;
+use crate::journal::{decode_times, encode_times, Action, Before, Entry};
+use crate::namespace::Namespace;
+use crate::progress::Episode;
+use crate::rating::Rating;
+use crate::status::Status;
+use chrono::{DateTime, Utc};
+use log::info;
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS catalogue (
+    kind  TEXT NOT NULL,
+    title TEXT NOT NULL,
+    PRIMARY KEY (kind, title)
+);
+CREATE TABLE IF NOT EXISTS catalogue_added (
+    kind     TEXT NOT NULL,
+    title    TEXT NOT NULL,
+    added_at INTEGER NOT NULL,
+    PRIMARY KEY (kind, title)
+);
+CREATE TABLE IF NOT EXISTS watched (
+    namespace TEXT NOT NULL,
+    kind      TEXT NOT NULL,
+    title     TEXT NOT NULL,
+    PRIMARY KEY (namespace, kind, title)
+);
+CREATE TABLE IF NOT EXISTS progress (
+    namespace TEXT NOT NULL,
+    title     TEXT NOT NULL,
+    season    INTEGER NOT NULL,
+    episode   INTEGER NOT NULL,
+    PRIMARY KEY (namespace, title)
+);
+CREATE TABLE IF NOT EXISTS statuses (
+    namespace TEXT NOT NULL,
+    kind      TEXT NOT NULL,
+    title     TEXT NOT NULL,
+    status    TEXT NOT NULL,
+    PRIMARY KEY (namespace, kind, title)
+);
+CREATE TABLE IF NOT EXISTS ratings (
+    namespace TEXT NOT NULL,
+    kind      TEXT NOT NULL,
+    title     TEXT NOT NULL,
+    score     INTEGER NOT NULL,
+    note      TEXT,
+    PRIMARY KEY (namespace, kind, title)
+);
+CREATE TABLE IF NOT EXISTS history (
+    namespace  TEXT NOT NULL,
+    kind       TEXT NOT NULL,
+    title      TEXT NOT NULL,
+    watched_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS history_by_namespace ON history (namespace, watched_at);
+CREATE TABLE IF NOT EXISTS journal (
+    namespace   TEXT NOT NULL,
+    id          INTEGER NOT NULL,
+    at          INTEGER NOT NULL,
+    user_id     INTEGER,
+    action      TEXT NOT NULL,
+    kind        TEXT NOT NULL,
+    title       TEXT NOT NULL,
+    was_watched INTEGER NOT NULL,
+    status      TEXT,
+    episode     TEXT,
+    score       INTEGER,
+    note        TEXT,
+    added_at    INTEGER,
+    line        TEXT,
+    history     TEXT NOT NULL,
+    batch       INTEGER,
+    PRIMARY KEY (namespace, id)
+);
+";
+
+/// Embedded database backend, for libraries that outgrow hand-edited files.
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+fn db_err(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+impl SqliteStorage {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path).map_err(db_err)?;
+        conn.execute_batch(SCHEMA).map_err(db_err)?;
+        Ok(SqliteStorage {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // SQLite rolls back a transaction that was never committed, so a
+        // connection left behind by a panicking thread is still consistent.
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Copies another backend into this database, so switching backends does
+    /// not start from an empty library. Only what the database has nothing
+    /// of yet is copied: the catalogue of each kind, the added dates, and
+    /// each namespace as a whole. Everything goes in one transaction.
+    pub fn seed_from(&self, other: &dyn Storage) -> io::Result<()> {
+        let mut catalogues = Vec::new();
+        let mut added = Vec::new();
+        for kind in Kind::ALL {
+            if self.catalogue(kind)?.is_empty() {
+                catalogues.push((kind, other.catalogue(kind)?));
+            }
+            if self.added_dates(kind)?.is_empty() {
+                added.push((kind, other.added_dates(kind)?));
+            }
+        }
+        let known: HashSet<Namespace> = self.namespaces()?.into_iter().collect();
+        let namespaces: Vec<Namespace> = other
+            .namespaces()?
+            .into_iter()
+            .filter(|ns| !known.contains(ns))
+            .collect();
+
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_err)?;
+        for (kind, titles) in &catalogues {
+            for title in titles {
+                tx.execute(
+                    "INSERT OR IGNORE INTO catalogue (kind, title) VALUES (?1, ?2)",
+                    params![kind.key(), title],
+                )
+                .map_err(db_err)?;
+            }
+            info!(
+                "Seeded {} {} into the database.",
+                titles.len(),
+                kind.plural()
+            );
+        }
+        for (kind, dates) in &added {
+            for (title, at) in dates {
+                tx.execute(
+                    "INSERT OR IGNORE INTO catalogue_added (kind, title, added_at)
+                     VALUES (?1, ?2, ?3)",
+                    params![kind.key(), title, at.timestamp()],
+                )
+                .map_err(db_err)?;
+            }
+        }
+        for ns in &namespaces {
+            seed_namespace(&tx, other, ns)?;
+        }
+        tx.commit().map_err(db_err)?;
+        if !namespaces.is_empty() {
+            info!(
+                "Seeded the lists of {} namespaces into the database.",
+                namespaces.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Copies every list of `ns` from `other`.
+fn seed_namespace(conn: &Connection, other: &dyn Storage, ns: &Namespace) -> io::Result<()> {
+    let key = ns.key();
+    for kind in Kind::ALL {
+        for title in other.watched(ns, kind)? {
+            conn.execute(
+                "INSERT OR IGNORE INTO watched (namespace, kind, title) VALUES (?1, ?2, ?3)",
+                params![key, kind.key(), title],
+            )
+            .map_err(db_err)?;
+        }
+        for (title, status) in other.statuses(ns, kind)? {
+            conn.execute(
+                "INSERT OR REPLACE INTO statuses (namespace, kind, title, status)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![key, kind.key(), title, status.key()],
+            )
+            .map_err(db_err)?;
+        }
+        for (title, rating) in other.ratings(ns, kind)? {
+            conn.execute(
+                "INSERT OR REPLACE INTO ratings (namespace, kind, title, score, note)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![key, kind.key(), title, rating.score, rating.note],
+            )
+            .map_err(db_err)?;
+        }
+    }
+    for (title, episode) in other.progress(ns)? {
+        conn.execute(
+            "INSERT OR REPLACE INTO progress (namespace, title, season, episode)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![key, title, episode.season, episode.episode],
+        )
+        .map_err(db_err)?;
+    }
+    for event in other.history(ns)? {
+        insert_watch(conn, ns, &event)?;
+    }
+    for entry in other.journal(ns)? {
+        insert_journal(conn, ns, &entry)?;
+    }
+    Ok(())
+}
+
+fn insert_watch(conn: &Connection, ns: &Namespace, event: &WatchEvent) -> io::Result<()> {
+    conn.execute(
+        "INSERT INTO history (namespace, kind, title, watched_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            ns.key(),
+            event.kind.key(),
+            event.title,
+            event.at.timestamp()
+        ],
+    )
+    .map_err(db_err)?;
+    Ok(())
+}
+
+fn insert_journal(conn: &Connection, ns: &Namespace, entry: &Entry) -> io::Result<()> {
+    let before = &entry.before;
+    let rating = before.rating.as_ref();
+    conn.execute(
+        "INSERT INTO journal (namespace, id, at, user_id, action, kind, title, was_watched,
+                              status, episode, score, note, added_at, line, history, batch)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            ns.key(),
+            entry.id,
+            entry.at.timestamp(),
+            entry.user,
+            entry.action.key(),
+            entry.kind.key(),
+            entry.title,
+            before.watched,
+            before.status.map(Status::key),
+            before.episode.map(|e| e.to_string()),
+            rating.map(|r| r.score),
+            rating.and_then(|r| r.note.as_deref()),
+            before.added_at.map(|at| at.timestamp()),
+            before.line,
+            encode_times(&before.history),
+            entry.batch,
+        ],
+    )
+    .map_err(db_err)?;
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn catalogue(&self, kind: Kind) -> io::Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT title FROM catalogue WHERE kind = ?1 ORDER BY title")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![kind.key()], |row| row.get(0))
+            .map_err(db_err)?;
+        rows.collect::<Result<_, _>>().map_err(db_err)
+    }
+
+    fn watched(&self, ns: &Namespace, kind: Kind) -> io::Result<HashSet<String>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT title FROM watched WHERE namespace = ?1 AND kind = ?2")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![ns.key(), kind.key()], |row| row.get(0))
+            .map_err(db_err)?;
+        rows.collect::<Result<_, _>>().map_err(db_err)
+    }
+
+    fn add(&self, kind: Kind, title: &str) -> io::Result<bool> {
+        let conn = self.conn();
+        let changed = conn
+            .execute(
+                "INSERT OR IGNORE INTO catalogue (kind, title) VALUES (?1, ?2)",
+                params![kind.key(), title],
+            )
+            .map_err(db_err)?;
+        Ok(changed > 0)
+    }
+
+    fn mark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool> {
+        let conn = self.conn();
+        let changed = conn
+            .execute(
+                "INSERT OR IGNORE INTO watched (namespace, kind, title) VALUES (?1, ?2, ?3)",
+                params![ns.key(), kind.key(), title],
+            )
+            .map_err(db_err)?;
+        Ok(changed > 0)
+    }
+
+    fn unmark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool> {
+        let conn = self.conn();
+        let changed = conn
+            .execute(
+                "DELETE FROM watched WHERE namespace = ?1 AND kind = ?2 AND title = ?3",
+                params![ns.key(), kind.key(), title],
+            )
+            .map_err(db_err)?;
+        Ok(changed > 0)
+    }
+
+    fn remove(&self, kind: Kind, title: &str) -> io::Result<bool> {
+        let conn = self.conn();
+        let changed = conn
+            .execute(
+                "DELETE FROM catalogue WHERE kind = ?1 AND title = ?2",
+                params![kind.key(), title],
+            )
+            .map_err(db_err)?;
+        Ok(changed > 0)
+    }
+
+    fn replace(&self, kind: Kind, old: &str, new: &str) -> io::Result<bool> {
+        let conn = self.conn();
+        let changed = conn
+            .execute(
+                "UPDATE OR IGNORE catalogue SET title = ?3 WHERE kind = ?1 AND title = ?2",
+                params![kind.key(), old, new],
+            )
+            .map_err(db_err)?;
+        Ok(changed > 0)
+    }
+
+    fn progress(&self, ns: &Namespace) -> io::Result<HashMap<String, Episode>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT title, season, episode FROM progress WHERE namespace = ?1")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![ns.key()], |row| {
+                Ok((
+                    row.get(0)?,
+                    Episode {
+                        season: row.get(1)?,
+                        episode: row.get(2)?,
+                    },
+                ))
+            })
+            .map_err(db_err)?;
+        rows.collect::<Result<_, _>>().map_err(db_err)
+    }
+
+    fn set_progress(
+        &self,
+        ns: &Namespace,
+        title: &str,
+        episode: Option<Episode>,
+    ) -> io::Result<()> {
+        let conn = self.conn();
+        match episode {
+            Some(episode) => conn.execute(
+                "INSERT OR REPLACE INTO progress (namespace, title, season, episode)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![ns.key(), title, episode.season, episode.episode],
+            ),
+            None => conn.execute(
+                "DELETE FROM progress WHERE namespace = ?1 AND title = ?2",
+                params![ns.key(), title],
+            ),
+        }
+        .map_err(db_err)?;
+        Ok(())
+    }
+
+    fn statuses(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Status>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT title, status FROM statuses WHERE namespace = ?1 AND kind = ?2")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![ns.key(), kind.key()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(db_err)?;
+        let mut statuses = HashMap::new();
+        for row in rows {
+            let (title, key) = row.map_err(db_err)?;
+            if let Some(status) = Status::from_key(&key) {
+                statuses.insert(title, status);
+            }
+        }
+        Ok(statuses)
+    }
+
+    fn set_status(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        status: Option<Status>,
+    ) -> io::Result<()> {
+        let conn = self.conn();
+        match status {
+            Some(status) => conn.execute(
+                "INSERT OR REPLACE INTO statuses (namespace, kind, title, status)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![ns.key(), kind.key(), title, status.key()],
+            ),
+            None => conn.execute(
+                "DELETE FROM statuses WHERE namespace = ?1 AND kind = ?2 AND title = ?3",
+                params![ns.key(), kind.key(), title],
+            ),
+        }
+        .map_err(db_err)?;
+        Ok(())
+    }
+
+    fn ratings(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Rating>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT title, score, note FROM ratings WHERE namespace = ?1 AND kind = ?2")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![ns.key(), kind.key()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, u8>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .map_err(db_err)?;
+        let mut ratings = HashMap::new();
+        for row in rows {
+            let (title, score, note) = row.map_err(db_err)?;
+            if let Some(rating) = Rating::new(score, note) {
+                ratings.insert(title, rating);
+            }
+        }
+        Ok(ratings)
+    }
+
+    fn set_rating(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        rating: Option<Rating>,
+    ) -> io::Result<()> {
+        let conn = self.conn();
+        match rating {
+            Some(rating) => conn.execute(
+                "INSERT OR REPLACE INTO ratings (namespace, kind, title, score, note)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![ns.key(), kind.key(), title, rating.score, rating.note],
+            ),
+            None => conn.execute(
+                "DELETE FROM ratings WHERE namespace = ?1 AND kind = ?2 AND title = ?3",
+                params![ns.key(), kind.key(), title],
+            ),
+        }
+        .map_err(db_err)?;
+        Ok(())
+    }
+
+    fn added_dates(&self, kind: Kind) -> io::Result<HashMap<String, DateTime<Utc>>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT title, added_at FROM catalogue_added WHERE kind = ?1")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![kind.key()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .map_err(db_err)?;
+        let mut dates = HashMap::new();
+        for row in rows {
+            let (title, at) = row.map_err(db_err)?;
+            if let Some(at) = DateTime::from_timestamp(at, 0) {
+                dates.insert(title, at);
+            }
+        }
+        Ok(dates)
+    }
+
+    fn record_added(&self, kind: Kind, title: &str, at: DateTime<Utc>) -> io::Result<()> {
+        self.conn()
+            .execute(
+                "INSERT OR REPLACE INTO catalogue_added (kind, title, added_at) VALUES (?1, ?2, ?3)",
+                params![kind.key(), title, at.timestamp()],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    fn history(&self, ns: &Namespace) -> io::Result<Vec<WatchEvent>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT kind, title, watched_at FROM history
+                 WHERE namespace = ?1 ORDER BY watched_at, rowid",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![ns.key()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })
+            .map_err(db_err)?;
+        let mut events = Vec::new();
+        for row in rows {
+            let (kind, title, at) = row.map_err(db_err)?;
+            if let (Some(kind), Some(at)) = (Kind::from_key(&kind), DateTime::from_timestamp(at, 0))
+            {
+                events.push(WatchEvent { kind, title, at });
+            }
+        }
+        Ok(events)
+    }
+
+    fn record_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()> {
+        insert_watch(&self.conn(), ns, event)
+    }
+
+    fn forget_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()> {
+        self.conn()
+            .execute(
+                "DELETE FROM history WHERE rowid = (
+                     SELECT MAX(rowid) FROM history
+                     WHERE namespace = ?1 AND kind = ?2 AND title = ?3 AND watched_at = ?4
+                 )",
+                params![
+                    ns.key(),
+                    event.kind.key(),
+                    event.title,
+                    event.at.timestamp()
+                ],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    fn forget_history(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<()> {
+        self.conn()
+            .execute(
+                "DELETE FROM history WHERE namespace = ?1 AND kind = ?2 AND title = ?3",
+                params![ns.key(), kind.key(), title],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    fn rename_everywhere(&self, kind: Kind, old: &str, new: &str) -> io::Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_err)?;
+        for table in [
+            "watched",
+            "statuses",
+            "ratings",
+            "history",
+            "catalogue_added",
+        ] {
+            tx.execute(
+                &format!(
+                    "UPDATE OR REPLACE {} SET title = ?3 WHERE kind = ?1 AND title = ?2",
+                    table
+                ),
+                params![kind.key(), old, new],
+            )
+            .map_err(db_err)?;
+        }
+        if kind == Kind::Series {
+            tx.execute(
+                "UPDATE OR REPLACE progress SET title = ?2 WHERE title = ?1",
+                params![old, new],
+            )
+            .map_err(db_err)?;
+        }
+        // Journal lines of additions, removals and renames carry tags.
+        tx.execute(
+            "UPDATE journal SET title = ?3 || substr(title, length(?2) + 1)
+             WHERE kind = ?1 AND (title = ?2 OR substr(title, 1, length(?2) + 3) = ?2 || ' | ')",
+            params![kind.key(), old, new],
+        )
+        .map_err(db_err)?;
+        tx.commit().map_err(db_err)
+    }
+
+    fn namespaces(&self) -> io::Result<Vec<Namespace>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT namespace FROM watched UNION SELECT namespace FROM progress
+                 UNION SELECT namespace FROM statuses UNION SELECT namespace FROM ratings
+                 UNION SELECT namespace FROM history UNION SELECT namespace FROM journal",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(db_err)?;
+        let mut namespaces = Vec::new();
+        for row in rows {
+            if let Some(ns) = Namespace::from_key(&row.map_err(db_err)?) {
+                namespaces.push(ns);
+            }
+        }
+        Ok(namespaces)
+    }
+
+    fn journal(&self, ns: &Namespace) -> io::Result<Vec<Entry>> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, at, user_id, action, kind, title, was_watched, status, episode,
+                        score, note, added_at, line, history, batch
+                 FROM journal WHERE namespace = ?1 ORDER BY id",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![ns.key()], |row| {
+                let rating = row
+                    .get::<_, Option<u8>>(9)?
+                    .and_then(|score| Rating::new(score, row.get(10).ok().flatten()));
+                Ok((
+                    row.get::<_, u64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, Option<u64>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    Before {
+                        watched: row.get(6)?,
+                        status: row
+                            .get::<_, Option<String>>(7)?
+                            .and_then(|s| Status::from_key(&s)),
+                        episode: row
+                            .get::<_, Option<String>>(8)?
+                            .and_then(|e| Episode::parse(&e)),
+                        rating,
+                        added_at: row
+                            .get::<_, Option<i64>>(11)?
+                            .and_then(|at| DateTime::from_timestamp(at, 0)),
+                        line: row.get(12)?,
+                        history: decode_times(&row.get::<_, String>(13)?),
+                    },
+                    row.get::<_, Option<u64>>(14)?,
+                ))
+            })
+            .map_err(db_err)?;
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, at, user, action, kind, title, before, batch) = row.map_err(db_err)?;
+            if let (Some(at), Some(action), Some(kind)) = (
+                DateTime::from_timestamp(at, 0),
+                Action::from_key(&action),
+                Kind::from_key(&kind),
+            ) {
+                entries.push(Entry {
+                    id,
+                    at,
+                    user,
+                    action,
+                    kind,
+                    title,
+                    before,
+                    batch,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    fn append_journal(&self, ns: &Namespace, entry: &Entry) -> io::Result<()> {
+        insert_journal(&self.conn(), ns, entry)
+    }
+
+    fn add_all(&self, kind: Kind, titles: &[String]) -> io::Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_err)?;
+        for title in titles {
+            tx.execute(
+                "INSERT OR IGNORE INTO catalogue (kind, title) VALUES (?1, ?2)",
+                params![kind.key(), title],
+            )
+            .map_err(db_err)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    fn record_added_all(&self, kind: Kind, titles: &[String], at: DateTime<Utc>) -> io::Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_err)?;
+        for title in titles {
+            tx.execute(
+                "INSERT OR REPLACE INTO catalogue_added (kind, title, added_at) VALUES (?1, ?2, ?3)",
+                params![kind.key(), title, at.timestamp()],
+            )
+            .map_err(db_err)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    fn mark_all_watched(&self, ns: &Namespace, kind: Kind, titles: &[String]) -> io::Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_err)?;
+        for title in titles {
+            tx.execute(
+                "INSERT OR IGNORE INTO watched (namespace, kind, title) VALUES (?1, ?2, ?3)",
+                params![ns.key(), kind.key(), title],
+            )
+            .map_err(db_err)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    fn record_watches(&self, ns: &Namespace, events: &[WatchEvent]) -> io::Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_err)?;
+        for event in events {
+            insert_watch(&tx, ns, event)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    fn append_journal_all(&self, ns: &Namespace, entries: &[Entry]) -> io::Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_err)?;
+        for entry in entries {
+            insert_journal(&tx, ns, entry)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::TextStorage;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn seeding_copies_every_list_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = TextStorage::new(dir.path(), dir.path().join("veri"));
+        let (alice, bob) = (Namespace::User(1), Namespace::Chat(-2));
+        let rating = Rating::new(8, Some("güzel".into())).unwrap();
+        text.add(Kind::Film, "Matilda (1996) | aile").unwrap();
+        text.add(Kind::Series, "Dark (3 Sezon)").unwrap();
+        text.record_added(Kind::Film, "Matilda (1996)", at(100))
+            .unwrap();
+        text.mark_watched(&alice, Kind::Film, "Matilda (1996)")
+            .unwrap();
+        text.set_rating(&alice, Kind::Film, "Matilda (1996)", Some(rating.clone()))
+            .unwrap();
+        text.set_status(&alice, Kind::Series, "Dark (3 Sezon)", Some(Status::Paused))
+            .unwrap();
+        let episode = Episode {
+            season: 2,
+            episode: 4,
+        };
+        text.set_progress(&alice, "Dark (3 Sezon)", Some(episode))
+            .unwrap();
+        let watch = WatchEvent {
+            kind: Kind::Film,
+            title: "Matilda (1996)".into(),
+            at: at(200),
+        };
+        text.record_watch(&alice, &watch).unwrap();
+        let entry = Entry {
+            id: 1,
+            at: at(200),
+            user: Some(1),
+            action: Action::Watched,
+            kind: Kind::Film,
+            title: "Matilda (1996)".into(),
+            before: Before {
+                watched: true,
+                line: Some("Matilda (1996) | aile".into()),
+                history: vec![at(100), at(150)],
+                ..Before::default()
+            },
+            batch: Some(1),
+        };
+        text.append_journal(&alice, &entry).unwrap();
+        assert_eq!(text.journal(&alice).unwrap(), vec![entry.clone()]);
+
+        let db = SqliteStorage::open(dir.path().join("kutuphane.db")).unwrap();
+        db.seed_from(&text).unwrap();
+        assert_eq!(
+            db.catalogue(Kind::Film).unwrap(),
+            vec!["Matilda (1996) | aile"]
+        );
+        assert_eq!(
+            db.added_dates(Kind::Film).unwrap()["Matilda (1996)"],
+            at(100)
+        );
+        assert!(db
+            .watched(&alice, Kind::Film)
+            .unwrap()
+            .contains("Matilda (1996)"));
+        assert_eq!(
+            db.ratings(&alice, Kind::Film).unwrap()["Matilda (1996)"],
+            rating
+        );
+        assert_eq!(
+            db.statuses(&alice, Kind::Series).unwrap()["Dark (3 Sezon)"],
+            Status::Paused
+        );
+        assert_eq!(db.progress(&alice).unwrap()["Dark (3 Sezon)"], episode);
+        assert_eq!(db.history(&alice).unwrap(), vec![watch.clone()]);
+        assert_eq!(db.journal(&alice).unwrap(), vec![entry]);
+
+        // Seeding again only picks up namespaces the database has not seen.
+        text.mark_watched(&bob, Kind::Series, "Dark (3 Sezon)")
+            .unwrap();
+        text.record_watch(&alice, &watch).unwrap();
+        db.seed_from(&text).unwrap();
+        assert_eq!(db.history(&alice).unwrap().len(), 1);
+        assert_eq!(db.watched(&bob, Kind::Series).unwrap().len(), 1);
+        let mut namespaces = db.namespaces().unwrap();
+        namespaces.sort_by_key(Namespace::key);
+        assert_eq!(namespaces, vec![alice, bob]);
+    }
+
+    #[test]
+    fn renames_and_removals_reach_every_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kutuphane.db");
+        let db = SqliteStorage::open(&path).unwrap();
+        let (ns, old, new) = (Namespace::User(1), "Drak (3 Sezon)", "Dark (3 Sezon)");
+        let series = Kind::Series;
+        db.add(series, old).unwrap();
+        db.record_added(series, old, at(100)).unwrap();
+        db.mark_watched(&ns, series, old).unwrap();
+        db.set_status(&ns, series, old, Some(Status::Dropped))
+            .unwrap();
+        db.set_rating(&ns, series, old, Rating::new(7, None))
+            .unwrap();
+        db.set_progress(
+            &ns,
+            old,
+            Some(Episode {
+                season: 1,
+                episode: 2,
+            }),
+        )
+        .unwrap();
+        let watch = WatchEvent {
+            kind: series,
+            title: old.into(),
+            at: at(200),
+        };
+        db.record_watch(&ns, &watch).unwrap();
+
+        assert!(db.replace(series, old, new).unwrap());
+        db.rename_everywhere(series, old, new).unwrap();
+        drop(db);
+        let db = SqliteStorage::open(&path).unwrap();
+        assert_eq!(db.catalogue(series).unwrap(), vec![new]);
+        assert!(db.added_dates(series).unwrap().contains_key(new));
+        assert!(db.watched(&ns, series).unwrap().contains(new));
+        assert!(db.statuses(&ns, series).unwrap().contains_key(new));
+        assert!(db.ratings(&ns, series).unwrap().contains_key(new));
+        assert!(db.progress(&ns).unwrap().contains_key(new));
+        assert_eq!(db.history(&ns).unwrap()[0].title, new);
+
+        assert!(db.remove(series, new).unwrap());
+        assert!(db.catalogue(series).unwrap().is_empty());
+        assert_eq!(db.history(&ns).unwrap().len(), 1);
+        db.forget_history(&ns, series, new).unwrap();
+        assert!(db.history(&ns).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,748 @@
+use super::{Kind, Storage};
+use crate::history::WatchEvent;
+use crate::journal::{decode_times, encode_times, renamed_title, Action, Before, Entry};
+use crate::namespace::Namespace;
+use crate::progress::Episode;
+use crate::rating::Rating;
+use crate::status::Status;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The original plain text layout: one title per line, catalogue files in
+/// `root` and one directory of watched lists per namespace under `data_dir`.
+pub struct TextStorage {
+    root: PathBuf,
+    data_dir: PathBuf,
+}
+
+impl TextStorage {
+    pub fn new(root: impl Into<PathBuf>, data_dir: impl Into<PathBuf>) -> Self {
+        TextStorage {
+            root: root.into(),
+            data_dir: data_dir.into(),
+        }
+    }
+
+    fn catalogue_path(&self, kind: Kind) -> PathBuf {
+        self.root.join(kind.catalogue_file())
+    }
+
+    fn watched_path(&self, ns: &Namespace, kind: Kind) -> PathBuf {
+        self.data_dir.join(ns.key()).join(kind.watched_file())
+    }
+
+    fn added_path(&self) -> PathBuf {
+        self.data_dir.join(ADDED_FILE)
+    }
+
+    fn namespace_path(&self, ns: &Namespace, file: &str) -> PathBuf {
+        self.data_dir.join(ns.key()).join(file)
+    }
+
+    /// Watched lists from before namespaces existed, when the bot kept one
+    /// list in `root` for everybody, that are still waiting to be moved.
+    pub fn legacy_lists(&self) -> Vec<(Kind, PathBuf)> {
+        Kind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.root.join(kind.watched_file())))
+            .filter(|(_, path)| path.is_file())
+            .collect()
+    }
+
+    /// Merges the legacy watched lists into `owner`'s and renames each file
+    /// to `*.tasindi`, so the move happens once and the original is kept.
+    /// Returns how many titles were new to `owner`.
+    pub fn adopt_legacy_lists(&self, owner: &Namespace) -> io::Result<usize> {
+        let mut adopted = 0;
+        for (kind, path) in self.legacy_lists() {
+            let titles = read_lines(&path)?;
+            let target = self.watched_path(owner, kind);
+            let mut watched = self.watched(owner, kind)?;
+            let before = watched.len();
+            watched.extend(titles);
+            write_sorted(&target, &watched)?;
+            adopted += watched.len() - before;
+            fs::rename(&path, path.with_extension("txt.tasindi"))?;
+            info!(
+                "Moved the legacy list '{}' to {} ({} new titles).",
+                path.display(),
+                owner,
+                watched.len() - before
+            );
+        }
+        Ok(adopted)
+    }
+
+    /// Directories of every namespace that has saved anything.
+    fn namespace_dirs(&self) -> io::Result<Vec<PathBuf>> {
+        let entries = match fs::read_dir(&self.data_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut dirs = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+        Ok(dirs)
+    }
+}
+
+const PROGRESS_FILE: &str = "ilerleme.txt";
+const STATUS_FILE: &str = "durumlar.txt";
+const RATINGS_FILE: &str = "puanlar.txt";
+const HISTORY_FILE: &str = "gecmis.txt";
+const JOURNAL_FILE: &str = "gunluk.txt";
+const JOURNAL_COLUMNS: usize = 15;
+/// Shared by every namespace, like the catalogue itself.
+const ADDED_FILE: &str = "eklenme_tarihleri.txt";
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A missing file is an empty list; any other read failure is an error, so a
+/// list that could not be read is never overwritten with a shorter one.
+fn read_contents(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            info!(
+                "'{}' dosyası bulunamadı. Boş liste döndürüldü.",
+                path.display()
+            );
+            Ok(String::new())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    Ok(read_contents(path)?
+        .lines()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().to_string())
+        .collect())
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into
+/// place, so readers only ever see the old or the new list.
+fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("liste");
+    let temp_path = dir.join(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Reads a tab separated file with `columns` fields per line. Lines with a
+/// different shape are skipped with a warning rather than failing the read.
+///
+/// Lines are not trimmed, since that would drop an empty last field.
+fn read_rows(path: &Path, columns: usize) -> io::Result<Vec<Vec<String>>> {
+    Ok(read_contents(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let row: Vec<String> = line.split('\t').map(str::to_string).collect();
+            if row.len() == columns {
+                Some(row)
+            } else {
+                warn!("'{}' içindeki '{}' satırı atlandı.", path.display(), line);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Refuses a field that would split its row or line in two. Input is
+/// cleaned up before it gets here, so this only guards against new callers.
+fn check_field(field: &str) -> io::Result<()> {
+    if field.contains(['\t', '\n', '\r']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{}' sekme veya satır sonu içeriyor", field.escape_debug()),
+        ));
+    }
+    Ok(())
+}
+
+/// Appends rows without rewriting the file, for logs that only grow.
+fn append_rows(path: &Path, rows: &[Vec<String>]) -> io::Result<()> {
+    let mut contents = String::new();
+    for row in rows {
+        row.iter().try_for_each(|field| check_field(field))?;
+        contents.push_str(&row.join("\t"));
+        contents.push('\n');
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+fn write_rows(path: &Path, rows: &[Vec<String>]) -> io::Result<()> {
+    let mut contents = String::new();
+    for row in rows {
+        row.iter().try_for_each(|field| check_field(field))?;
+        contents.push_str(&row.join("\t"));
+        contents.push('\n');
+    }
+    write_atomic(path, &contents)
+}
+
+/// Renames or (with `new == None`) drops the rows of `path` whose column
+/// `title_col` holds `old`; with `kind` set as `(column, key)`, only rows of
+/// that kind. Files without such rows are left untouched.
+fn rewrite_title_rows(
+    path: &Path,
+    columns: usize,
+    kind: Option<(usize, &str)>,
+    title_col: usize,
+    old: &str,
+    new: Option<&str>,
+) -> io::Result<()> {
+    let rows = read_rows(path, columns)?;
+    let matches =
+        |row: &Vec<String>| row[title_col] == old && kind.is_none_or(|(col, key)| row[col] == key);
+    if !rows.iter().any(matches) {
+        return Ok(());
+    }
+    let mut rewritten: Vec<Vec<String>> = Vec::with_capacity(rows.len());
+    for mut row in rows {
+        if !matches(&row) {
+            rewritten.push(row);
+        } else if let Some(new) = new {
+            row[title_col] = new.to_string();
+            rewritten.push(row);
+        }
+    }
+    rewritten.sort();
+    rewritten.dedup();
+    write_rows(path, &rewritten)
+}
+
+/// Renames a title in a file of events rather than a set, whose rows keep
+/// their order and repeats.
+fn rename_in_events(
+    path: &Path,
+    columns: usize,
+    (kind_col, key): (usize, &str),
+    title_col: usize,
+    rename: impl Fn(&str) -> Option<String>,
+) -> io::Result<()> {
+    let mut rows = read_rows(path, columns)?;
+    let mut changed = false;
+    for row in rows.iter_mut().filter(|row| row[kind_col] == key) {
+        if let Some(title) = rename(&row[title_col]) {
+            row[title_col] = title;
+            changed = true;
+        }
+    }
+    if changed {
+        write_rows(path, &rows)?;
+    }
+    Ok(())
+}
+
+fn history_row(event: &WatchEvent) -> Vec<String> {
+    vec![
+        event.at.timestamp().to_string(),
+        event.kind.key().to_string(),
+        event.title.clone(),
+    ]
+}
+
+fn journal_row(entry: &Entry) -> Vec<String> {
+    let before = &entry.before;
+    let rating = before.rating.as_ref();
+    vec![
+        entry.id.to_string(),
+        entry.at.timestamp().to_string(),
+        entry.user.map(|u| u.to_string()).unwrap_or_default(),
+        entry.action.key(),
+        entry.kind.key().to_string(),
+        entry.title.clone(),
+        if before.watched { "1" } else { "0" }.to_string(),
+        before
+            .status
+            .map(|s| s.key().to_string())
+            .unwrap_or_default(),
+        before.episode.map(|e| e.to_string()).unwrap_or_default(),
+        rating.map(|r| r.score.to_string()).unwrap_or_default(),
+        rating.and_then(|r| r.note.clone()).unwrap_or_default(),
+        before
+            .added_at
+            .map(|at| at.timestamp().to_string())
+            .unwrap_or_default(),
+        before.line.clone().unwrap_or_default(),
+        encode_times(&before.history),
+        entry.batch.map(|b| b.to_string()).unwrap_or_default(),
+    ]
+}
+
+fn write_sorted(path: &Path, lines: &HashSet<String>) -> io::Result<()> {
+    let mut sorted: Vec<&String> = lines.iter().collect();
+    sorted.sort();
+    let mut contents = String::new();
+    for line in sorted {
+        check_field(line)?;
+        contents.push_str(line);
+        contents.push('\n');
+    }
+    write_atomic(path, &contents)
+}
+
+impl Storage for TextStorage {
+    fn catalogue(&self, kind: Kind) -> io::Result<Vec<String>> {
+        read_lines(&self.catalogue_path(kind))
+    }
+
+    fn watched(&self, ns: &Namespace, kind: Kind) -> io::Result<HashSet<String>> {
+        Ok(read_lines(&self.watched_path(ns, kind))?
+            .into_iter()
+            .collect())
+    }
+
+    fn add(&self, kind: Kind, title: &str) -> io::Result<bool> {
+        let path = self.catalogue_path(kind);
+        let mut all: HashSet<String> = read_lines(&path)?.into_iter().collect();
+        if !all.insert(title.to_string()) {
+            return Ok(false);
+        }
+        write_sorted(&path, &all)?;
+        Ok(true)
+    }
+
+    fn mark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool> {
+        let path = self.watched_path(ns, kind);
+        let mut watched: HashSet<String> = read_lines(&path)?.into_iter().collect();
+        if !watched.insert(title.to_string()) {
+            return Ok(false);
+        }
+        write_sorted(&path, &watched)?;
+        Ok(true)
+    }
+
+    fn unmark_watched(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<bool> {
+        let path = self.watched_path(ns, kind);
+        let mut watched: HashSet<String> = read_lines(&path)?.into_iter().collect();
+        if !watched.remove(title) {
+            return Ok(false);
+        }
+        write_sorted(&path, &watched)?;
+        Ok(true)
+    }
+
+    fn remove(&self, kind: Kind, title: &str) -> io::Result<bool> {
+        let path = self.catalogue_path(kind);
+        let mut all: HashSet<String> = read_lines(&path)?.into_iter().collect();
+        if !all.remove(title) {
+            return Ok(false);
+        }
+        write_sorted(&path, &all)?;
+        Ok(true)
+    }
+
+    fn replace(&self, kind: Kind, old: &str, new: &str) -> io::Result<bool> {
+        let path = self.catalogue_path(kind);
+        let mut all: HashSet<String> = read_lines(&path)?.into_iter().collect();
+        if all.contains(new) || !all.remove(old) {
+            return Ok(false);
+        }
+        all.insert(new.to_string());
+        write_sorted(&path, &all)?;
+        Ok(true)
+    }
+
+    fn progress(&self, ns: &Namespace) -> io::Result<HashMap<String, Episode>> {
+        let path = self.namespace_path(ns, PROGRESS_FILE);
+        Ok(read_rows(&path, 2)?
+            .into_iter()
+            .filter_map(|row| Some((row[0].clone(), Episode::parse(&row[1])?)))
+            .collect())
+    }
+
+    fn set_progress(
+        &self,
+        ns: &Namespace,
+        title: &str,
+        episode: Option<Episode>,
+    ) -> io::Result<()> {
+        let path = self.namespace_path(ns, PROGRESS_FILE);
+        let mut rows: Vec<Vec<String>> = read_rows(&path, 2)?
+            .into_iter()
+            .filter(|row| row[0] != title)
+            .collect();
+        if let Some(episode) = episode {
+            rows.push(vec![title.to_string(), episode.to_string()]);
+        }
+        rows.sort();
+        write_rows(&path, &rows)
+    }
+
+    fn statuses(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Status>> {
+        let path = self.namespace_path(ns, STATUS_FILE);
+        Ok(read_rows(&path, 3)?
+            .into_iter()
+            .filter(|row| row[0] == kind.key())
+            .filter_map(|row| Some((row[1].clone(), Status::from_key(&row[2])?)))
+            .collect())
+    }
+
+    fn set_status(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        status: Option<Status>,
+    ) -> io::Result<()> {
+        let path = self.namespace_path(ns, STATUS_FILE);
+        let mut rows: Vec<Vec<String>> = read_rows(&path, 3)?
+            .into_iter()
+            .filter(|row| !(row[0] == kind.key() && row[1] == title))
+            .collect();
+        if let Some(status) = status {
+            rows.push(vec![
+                kind.key().to_string(),
+                title.to_string(),
+                status.key().to_string(),
+            ]);
+        }
+        rows.sort();
+        write_rows(&path, &rows)
+    }
+
+    fn ratings(&self, ns: &Namespace, kind: Kind) -> io::Result<HashMap<String, Rating>> {
+        let path = self.namespace_path(ns, RATINGS_FILE);
+        Ok(read_rows(&path, 4)?
+            .into_iter()
+            .filter(|row| row[0] == kind.key())
+            .filter_map(|row| {
+                let note = Some(row[3].clone()).filter(|n| !n.is_empty());
+                Some((row[1].clone(), Rating::new(row[2].parse().ok()?, note)?))
+            })
+            .collect())
+    }
+
+    fn set_rating(
+        &self,
+        ns: &Namespace,
+        kind: Kind,
+        title: &str,
+        rating: Option<Rating>,
+    ) -> io::Result<()> {
+        let path = self.namespace_path(ns, RATINGS_FILE);
+        let mut rows: Vec<Vec<String>> = read_rows(&path, 4)?
+            .into_iter()
+            .filter(|row| !(row[0] == kind.key() && row[1] == title))
+            .collect();
+        if let Some(rating) = rating {
+            rows.push(vec![
+                kind.key().to_string(),
+                title.to_string(),
+                rating.score.to_string(),
+                rating.note.unwrap_or_default(),
+            ]);
+        }
+        rows.sort();
+        write_rows(&path, &rows)
+    }
+
+    fn added_dates(&self, kind: Kind) -> io::Result<HashMap<String, DateTime<Utc>>> {
+        Ok(read_rows(&self.added_path(), 3)?
+            .into_iter()
+            .filter(|row| row[0] == kind.key())
+            .filter_map(|row| {
+                Some((
+                    row[1].clone(),
+                    DateTime::from_timestamp(row[2].parse().ok()?, 0)?,
+                ))
+            })
+            .collect())
+    }
+
+    fn record_added(&self, kind: Kind, title: &str, at: DateTime<Utc>) -> io::Result<()> {
+        let path = self.added_path();
+        let mut rows: Vec<Vec<String>> = read_rows(&path, 3)?
+            .into_iter()
+            .filter(|row| !(row[0] == kind.key() && row[1] == title))
+            .collect();
+        rows.push(vec![
+            kind.key().to_string(),
+            title.to_string(),
+            at.timestamp().to_string(),
+        ]);
+        rows.sort();
+        write_rows(&path, &rows)
+    }
+
+    fn history(&self, ns: &Namespace) -> io::Result<Vec<WatchEvent>> {
+        let path = self.namespace_path(ns, HISTORY_FILE);
+        let mut events: Vec<WatchEvent> = read_rows(&path, 3)?
+            .into_iter()
+            .filter_map(|row| {
+                Some(WatchEvent {
+                    at: DateTime::from_timestamp(row[0].parse().ok()?, 0)?,
+                    kind: Kind::from_key(&row[1])?,
+                    title: row[2].clone(),
+                })
+            })
+            .collect();
+        events.sort_by_key(|e| e.at);
+        Ok(events)
+    }
+
+    fn record_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()> {
+        let path = self.namespace_path(ns, HISTORY_FILE);
+        let mut rows = read_rows(&path, 3)?;
+        rows.push(history_row(event));
+        write_rows(&path, &rows)
+    }
+
+    fn forget_watch(&self, ns: &Namespace, event: &WatchEvent) -> io::Result<()> {
+        let path = self.namespace_path(ns, HISTORY_FILE);
+        let mut rows = read_rows(&path, 3)?;
+        let row = history_row(event);
+        let Some(index) = rows.iter().rposition(|r| *r == row) else {
+            return Ok(());
+        };
+        rows.remove(index);
+        write_rows(&path, &rows)
+    }
+
+    fn forget_history(&self, ns: &Namespace, kind: Kind, title: &str) -> io::Result<()> {
+        let path = self.namespace_path(ns, HISTORY_FILE);
+        rewrite_title_rows(&path, 3, Some((1, kind.key())), 2, title, None)
+    }
+
+    fn rename_everywhere(&self, kind: Kind, old: &str, new: &str) -> io::Result<()> {
+        let key = kind.key();
+        let to = Some(new);
+        for dir in self.namespace_dirs()? {
+            rewrite_title_rows(&dir.join(kind.watched_file()), 1, None, 0, old, to)?;
+            rewrite_title_rows(&dir.join(STATUS_FILE), 3, Some((0, key)), 1, old, to)?;
+            rewrite_title_rows(&dir.join(RATINGS_FILE), 4, Some((0, key)), 1, old, to)?;
+            if kind == Kind::Series {
+                rewrite_title_rows(&dir.join(PROGRESS_FILE), 2, None, 0, old, to)?;
+            }
+            let history = dir.join(HISTORY_FILE);
+            rename_in_events(&history, 3, (1, key), 2, |t| {
+                (t == old).then(|| new.to_string())
+            })?;
+            let journal = dir.join(JOURNAL_FILE);
+            rename_in_events(&journal, JOURNAL_COLUMNS, (4, key), 5, |t| {
+                renamed_title(t, old, new)
+            })?;
+        }
+        rewrite_title_rows(&self.added_path(), 3, Some((0, key)), 1, old, to)
+    }
+
+    fn namespaces(&self) -> io::Result<Vec<Namespace>> {
+        Ok(self
+            .namespace_dirs()?
+            .iter()
+            .filter_map(|dir| Namespace::from_key(dir.file_name()?.to_str()?))
+            .collect())
+    }
+
+    fn journal(&self, ns: &Namespace) -> io::Result<Vec<Entry>> {
+        let path = self.namespace_path(ns, JOURNAL_FILE);
+        Ok(read_rows(&path, JOURNAL_COLUMNS)?
+            .into_iter()
+            .filter_map(|row| {
+                let timestamp = |col: usize| -> Option<Option<DateTime<Utc>>> {
+                    match row[col].as_str() {
+                        "" => Some(None),
+                        ts => Some(Some(DateTime::from_timestamp(ts.parse().ok()?, 0)?)),
+                    }
+                };
+                let rating = match row[9].as_str() {
+                    "" => None,
+                    score => Rating::new(score.parse().ok()?, Some(row[10].clone())),
+                };
+                Some(Entry {
+                    id: row[0].parse().ok()?,
+                    at: timestamp(1)??,
+                    user: row[2].parse().ok(),
+                    action: Action::from_key(&row[3])?,
+                    kind: Kind::from_key(&row[4])?,
+                    title: row[5].clone(),
+                    before: Before {
+                        watched: row[6] == "1",
+                        status: Status::from_key(&row[7]),
+                        episode: Episode::parse(&row[8]),
+                        rating,
+                        added_at: timestamp(11)?,
+                        line: Some(row[12].clone()).filter(|line| !line.is_empty()),
+                        history: decode_times(&row[13]),
+                    },
+                    batch: row[14].parse().ok(),
+                })
+            })
+            .collect())
+    }
+
+    fn append_journal(&self, ns: &Namespace, entry: &Entry) -> io::Result<()> {
+        self.append_journal_all(ns, std::slice::from_ref(entry))
+    }
+
+    fn add_all(&self, kind: Kind, titles: &[String]) -> io::Result<()> {
+        let path = self.catalogue_path(kind);
+        let mut all: HashSet<String> = read_lines(&path)?.into_iter().collect();
+        all.extend(titles.iter().cloned());
+        write_sorted(&path, &all)
+    }
+
+    fn record_added_all(&self, kind: Kind, titles: &[String], at: DateTime<Utc>) -> io::Result<()> {
+        let path = self.added_path();
+        let titles: HashSet<&String> = titles.iter().collect();
+        let mut rows: Vec<Vec<String>> = read_rows(&path, 3)?
+            .into_iter()
+            .filter(|row| !(row[0] == kind.key() && titles.contains(&row[1])))
+            .collect();
+        for title in titles {
+            rows.push(vec![
+                kind.key().to_string(),
+                title.clone(),
+                at.timestamp().to_string(),
+            ]);
+        }
+        rows.sort();
+        write_rows(&path, &rows)
+    }
+
+    fn mark_all_watched(&self, ns: &Namespace, kind: Kind, titles: &[String]) -> io::Result<()> {
+        let path = self.watched_path(ns, kind);
+        let mut watched: HashSet<String> = read_lines(&path)?.into_iter().collect();
+        watched.extend(titles.iter().cloned());
+        write_sorted(&path, &watched)
+    }
+
+    fn record_watches(&self, ns: &Namespace, events: &[WatchEvent]) -> io::Result<()> {
+        let path = self.namespace_path(ns, HISTORY_FILE);
+        let mut rows = read_rows(&path, 3)?;
+        rows.extend(events.iter().map(history_row));
+        write_rows(&path, &rows)
+    }
+
+    fn append_journal_all(&self, ns: &Namespace, entries: &[Entry]) -> io::Result<()> {
+        let rows: Vec<Vec<String>> = entries.iter().map(journal_row).collect();
+        append_rows(&self.namespace_path(ns, JOURNAL_FILE), &rows)
+    }
+
+    fn source_files(&self) -> Vec<(Kind, PathBuf)> {
+        Kind::ALL
+            .iter()
+            .map(|&kind| (kind, self.catalogue_path(kind)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn atomic_writes_replace_the_whole_file_and_clean_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("liste").join("filmler.txt");
+        assert_eq!(read_contents(&path).unwrap(), "");
+
+        write_atomic(&path, "Uzun Bir Film Adı (2001)\nİkinci (2002)\n").unwrap();
+        write_atomic(&path, "Kısa (2003)\n").unwrap();
+        assert_eq!(read_contents(&path).unwrap(), "Kısa (2003)\n");
+        assert_eq!(read_lines(&path).unwrap(), vec!["Kısa (2003)"]);
+        assert_eq!(entries(path.parent().unwrap()), vec!["filmler.txt"]);
+    }
+
+    #[test]
+    fn legacy_lists_move_to_their_owner_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = TextStorage::new(dir.path(), dir.path().join("veri"));
+        let (owner, other) = (Namespace::User(1), Namespace::User(2));
+        fs::write(
+            dir.path().join("izlenen_filmler.txt"),
+            "Matilda (1996)\nAlien (1979)\n",
+        )
+        .unwrap();
+        storage
+            .mark_watched(&owner, Kind::Film, "Alien (1979)")
+            .unwrap();
+
+        assert_eq!(storage.legacy_lists().len(), 1);
+        assert_eq!(storage.adopt_legacy_lists(&owner).unwrap(), 1);
+        assert_eq!(storage.watched(&owner, Kind::Film).unwrap().len(), 2);
+        assert!(storage.watched(&other, Kind::Film).unwrap().is_empty());
+        assert!(storage.legacy_lists().is_empty());
+        assert!(dir.path().join("izlenen_filmler.txt.tasindi").is_file());
+        assert_eq!(storage.adopt_legacy_lists(&other).unwrap(), 0);
+    }
+
+    #[test]
+    fn failed_writes_and_reads_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        // A non-empty directory where the list should go cannot be replaced.
+        let path = dir.path().join("filmler.txt");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("icerik"), "").unwrap();
+
+        assert!(write_atomic(&path, "Matilda (1996)\n").is_err());
+        assert_eq!(entries(dir.path()), vec!["filmler.txt"]);
+        assert!(read_contents(&path).is_err());
+        assert!(write_atomic(&path.join("icerik").join("alt.txt"), "").is_err());
+    }
+
+    #[test]
+    fn rows_refuse_fields_with_tabs_or_line_breaks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("puanlar.txt");
+        let row = |note: &str| vec!["film".to_string(), "Matilda (1996)".into(), note.into()];
+        append_rows(&path, &[row("güzel")]).unwrap();
+        for note in ["iki\tsütun", "iki\nsatır"] {
+            assert!(append_rows(&path, &[row(note)]).is_err());
+            assert!(write_rows(&path, &[row(note)]).is_err());
+        }
+        assert_eq!(read_rows(&path, 3).unwrap(), vec![row("güzel")]);
+    }
+}
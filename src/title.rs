@@ -0,0 +1,270 @@
+use crate::normalize::{fold, single_line};
+use crate::storage::Kind;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Separates a catalogue line from its tags: `Matilda (1996) | komedi, aile`.
+const TAG_SEPARATOR: char = '|';
+
+/// A catalogue line split into its parts: `13 Günah (2014)` for films and
+/// `9-1-1 (9 Sezon)` for series, optionally followed by tags. Lines that do
+/// not follow either shape keep their whole text as the name, so
+/// `to_string()` always gives back the original line without its tags.
+///
+/// Tags are not part of a title's identity: `to_string()` is what the watched
+/// lists store, and two titles are equal whatever their tags.
+#[derive(Clone, Debug)]
+pub struct Title {
+    pub name: String,
+    pub year: Option<u16>,
+    pub seasons: Option<u32>,
+    pub tags: Vec<String>,
+}
+
+impl PartialEq for Title {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.name, self.year, self.seasons) == (&other.name, other.year, other.seasons)
+    }
+}
+
+impl Eq for Title {}
+
+impl Hash for Title {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (&self.name, self.year, self.seasons).hash(state);
+    }
+}
+
+impl Title {
+    pub fn parse(line: &str) -> Self {
+        let line = single_line(line);
+        let (line, tags) = match line.split_once(TAG_SEPARATOR) {
+            Some((line, tags)) => (line.trim(), parse_tags(tags)),
+            None => (line.trim(), Vec::new()),
+        };
+        let unparsed = || Title {
+            name: line.to_string(),
+            year: None,
+            seasons: None,
+            tags: tags.clone(),
+        };
+
+        let Some((name, rest)) = line.rsplit_once(" (") else {
+            return unparsed();
+        };
+        let Some(inner) = rest.strip_suffix(')') else {
+            return unparsed();
+        };
+        if name.trim().is_empty() {
+            return unparsed();
+        }
+
+        if inner.len() == 4 && inner.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(year) = inner.parse() {
+                return Title {
+                    name: name.to_string(),
+                    year: Some(year),
+                    seasons: None,
+                    tags,
+                };
+            }
+        }
+        if let Some(count) = inner.strip_suffix(" Sezon") {
+            if let Ok(seasons) = count.parse::<u32>() {
+                // Only accept the canonical spelling so the line round-trips.
+                if seasons.to_string() == count {
+                    return Title {
+                        name: name.to_string(),
+                        year: None,
+                        seasons: Some(seasons),
+                        tags,
+                    };
+                }
+            }
+        }
+        unparsed()
+    }
+
+    /// The catalogue line for this title, tags included.
+    pub fn line(&self) -> String {
+        if self.tags.is_empty() {
+            self.to_string()
+        } else {
+            format!("{} {} {}", self, TAG_SEPARATOR, self.tags.join(", "))
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = fold(tag);
+        self.tags.iter().any(|t| fold(t) == tag)
+    }
+
+    /// Describes what is missing from a line of the given catalogue, if
+    /// anything.
+    pub fn problem(&self, kind: Kind) -> Option<&'static str> {
+        match kind {
+            Kind::Film if self.year.is_none() => Some("yıl bilgisi eksik, örn: `Matilda (1996)`"),
+            Kind::Series if self.seasons.is_none() => {
+                Some("sezon bilgisi eksik, örn: `Zamanın Kapıları (2 Sezon)`")
+            }
+            _ => None,
+        }
+    }
+
+    /// Match on the name alone, so `13 gunah` finds `13 Günah (2014)`.
+    /// `input_folded` has gone through [`fold`].
+    pub fn is_named(&self, input_folded: &str) -> bool {
+        fold(&self.name) == input_folded
+    }
+
+    /// Whether both are the same entry up to case and diacritics, which is
+    /// how the catalogue detects duplicates.
+    pub fn same_as(&self, other: &Title) -> bool {
+        fold(&self.to_string()) == fold(&other.to_string())
+    }
+
+    /// Oldest first; titles without a year go last.
+    pub fn cmp_by_year(&self, other: &Self) -> Ordering {
+        match (self.year, other.year) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+        .then_with(|| self.name.cmp(&other.name))
+    }
+
+    /// Most seasons first; titles without a season count go last.
+    pub fn cmp_by_seasons(&self, other: &Self) -> Ordering {
+        other
+            .seasons
+            .cmp(&self.seasons)
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+impl fmt::Display for Title {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.year, self.seasons) {
+            (Some(year), _) => write!(f, "{} ({})", self.name, year),
+            (None, Some(seasons)) => write!(f, "{} ({} Sezon)", self.name, seasons),
+            (None, None) => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Splits `korku, gerilim` into lowercase tags, dropping empty and repeated
+/// ones.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in single_line(text)
+        .split(',')
+        .map(|t| t.trim().to_lowercase())
+    {
+        if !tag.is_empty() && !tags.iter().any(|t| fold(t) == fold(&tag)) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Order of the list commands, picked by their optional argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListOrder {
+    Name,
+    Year,
+    Seasons,
+}
+
+impl ListOrder {
+    pub fn parse(arg: &str) -> Option<Self> {
+        match arg.trim().to_lowercase().as_str() {
+            "" | "ad" | "isim" => Some(ListOrder::Name),
+            "yil" | "yıl" => Some(ListOrder::Year),
+            "sezon" => Some(ListOrder::Seasons),
+            _ => None,
+        }
+    }
+
+    /// The word [`ListOrder::parse`] reads back.
+    pub fn key(self) -> &'static str {
+        match self {
+            ListOrder::Name => "ad",
+            ListOrder::Year => "yil",
+            ListOrder::Seasons => "sezon",
+        }
+    }
+
+    pub fn sort(self, titles: &mut [Title]) {
+        self.sort_by(titles, |t| t);
+    }
+
+    /// Sorts anything that carries a title, e.g. `(Title, Status)` pairs.
+    pub fn sort_by<T>(self, items: &mut [T], title: impl Fn(&T) -> &Title) {
+        match self {
+            ListOrder::Name => items.sort_by_key(|i| title(i).to_string()),
+            ListOrder::Year => items.sort_by(|a, b| title(a).cmp_by_year(title(b))),
+            ListOrder::Seasons => items.sort_by(|a, b| title(a).cmp_by_seasons(title(b))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_characters_do_not_reach_lines_or_tags() {
+        let title = Title::parse("Matilda\t(1996) | aile\nkomedi, dram\r");
+        assert_eq!(title.to_string(), "Matilda (1996)");
+        assert_eq!(title.tags, vec!["aile komedi", "dram"]);
+        assert_eq!(parse_tags("korku\tgerilim"), vec!["korku gerilim"]);
+    }
+
+    #[test]
+    fn parses_and_round_trips_catalogue_lines() {
+        let film = Title::parse("13 Günah (2014)");
+        assert_eq!(film.name, "13 Günah");
+        assert_eq!(film.year, Some(2014));
+        assert_eq!(film.to_string(), "13 Günah (2014)");
+
+        let series = Title::parse("9-1-1 (9 Sezon)");
+        assert_eq!(series.name, "9-1-1");
+        assert_eq!(series.seasons, Some(9));
+        assert_eq!(series.to_string(), "9-1-1 (9 Sezon)");
+
+        for line in [
+            "Iron Man",
+            "Foo (bar)",
+            "Bar (09 Sezon)",
+            "(2014)",
+            "Baz (20145)",
+        ] {
+            let title = Title::parse(line);
+            assert_eq!(title.to_string(), line);
+            assert_eq!(title.year, None);
+            assert_eq!(title.seasons, None);
+        }
+    }
+
+    #[test]
+    fn tags_are_kept_apart_from_the_title() {
+        let film = Title::parse("Matilda (1996) | Komedi, aile, komedi");
+        assert_eq!(film.to_string(), "Matilda (1996)");
+        assert_eq!(film.tags, vec!["komedi", "aile"]);
+        assert_eq!(film.line(), "Matilda (1996) | komedi, aile");
+        assert_eq!(film, Title::parse("Matilda (1996)"));
+        assert_eq!(Title::parse("Iron Man | aksiyon").name, "Iron Man");
+    }
+
+    #[test]
+    fn reports_missing_details() {
+        assert!(Title::parse("Iron Man").problem(Kind::Film).is_some());
+        assert!(Title::parse("Matilda (1996)").problem(Kind::Film).is_none());
+        assert!(Title::parse("Dark (3 Sezon)")
+            .problem(Kind::Series)
+            .is_none());
+        assert!(Title::parse("Dark").problem(Kind::Series).is_some());
+    }
+}
@@ -0,0 +1,99 @@
+use crate::library::Library;
+use crate::storage::Kind;
+use log::{error, info, warn};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for a burst of events from a single save to settle.
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+/// Reloads the in-memory catalogue whenever one of the library's source files
+/// changes on disk. The returned watcher stops when dropped, so keep it alive.
+pub fn spawn(library: Library) -> notify::Result<Option<RecommendedWatcher>> {
+    let mut targets: Vec<(Kind, PathBuf)> = Vec::new();
+    let mut dirs: HashSet<PathBuf> = HashSet::new();
+    for (kind, path) in library.source_files() {
+        // Editors (and our own atomic writes) replace the file instead of
+        // writing into it, so watch the directory and match on the path.
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let dir = fs::canonicalize(&dir)?;
+        if let Some(name) = path.file_name() {
+            targets.push((kind, dir.join(name)));
+        }
+        dirs.insert(dir);
+    }
+    if targets.is_empty() {
+        return Ok(None);
+    }
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for dir in &dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        info!("Watching '{}' for catalogue changes.", dir.display());
+    }
+
+    thread::spawn(move || {
+        let changed_kinds = |event: notify::Result<Event>| -> Vec<Kind> {
+            match event {
+                Ok(event) => targets
+                    .iter()
+                    .filter(|(_, target)| event.paths.contains(target))
+                    .map(|(kind, _)| *kind)
+                    .collect(),
+                Err(e) => {
+                    warn!("File watcher error: {}", e);
+                    Vec::new()
+                }
+            }
+        };
+
+        while let Ok(event) = rx.recv() {
+            let mut changed: HashSet<Kind> = changed_kinds(event).into_iter().collect();
+            if changed.is_empty() {
+                continue;
+            }
+            while let Ok(event) = rx.recv_timeout(SETTLE_DELAY) {
+                changed.extend(changed_kinds(event));
+            }
+            for kind in changed {
+                if let Err(e) = library.reload(kind) {
+                    error!("Failed to reload {}: {}", kind.catalogue_file(), e);
+                }
+            }
+        }
+    });
+
+    Ok(Some(watcher))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn edits_to_the_catalogue_file_are_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(Kind::Film.catalogue_file());
+        fs::write(&path, "Alien (1979)\n").unwrap();
+        let library = Library::in_dir(dir.path());
+        let _watcher = spawn(library.clone()).unwrap().unwrap();
+
+        fs::write(&path, "Alien (1979)\nHeat (1995)\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while library.catalogue(Kind::Film).len() < 2 {
+            assert!(Instant::now() < deadline, "the catalogue was not reloaded");
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(library.catalogue(Kind::Film)[1].name, "Heat");
+    }
+}